@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rtsched_rs::{clock_is_vdso_accelerated, get_time, ClockId};
+
+const CLOCKS: [ClockId; 11] = [
+    ClockId::ClockRealtime,
+    ClockId::ClockRealtimeAlarm,
+    ClockId::ClockRealtimeCoarse,
+    ClockId::ClockTai,
+    ClockId::ClockMonotonic,
+    ClockId::ClockMonotonicCoarse,
+    ClockId::ClockMonotonicRaw,
+    ClockId::ClockBoottime,
+    ClockId::ClockBoottimeAlarm,
+    ClockId::ClockProcessCputimeId,
+    ClockId::ClockThreadCputimeId,
+];
+
+/// Compares `get_time()`, which always enters the kernel, with the vDSO backed
+/// `clock_gettime()` of the C library.
+fn bench_clock_gettime(c: &mut Criterion) {
+    for clockid in CLOCKS {
+        let mut group = c.benchmark_group(format!(
+            "{clockid:?} (vdso: {})",
+            clock_is_vdso_accelerated(clockid)
+        ));
+        group.bench_function("get_time", |b| b.iter(|| get_time(black_box(clockid))));
+        group.bench_function("libc::clock_gettime", |b| {
+            let mut tp = libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            };
+            b.iter(|| unsafe { libc::clock_gettime(black_box(clockid.as_raw()), &mut tp) })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_clock_gettime);
+criterion_main!(benches);
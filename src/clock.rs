@@ -1,10 +1,12 @@
+use std::{collections::HashMap, fs, io};
+
 use syscalls::Errno;
 
 use crate::lowlevel::clock::{
-    clock_gettime, clock_nanosleep, clock_settime, clockid_t, TimeSpec, CLOCK_BOOTTIME,
-    CLOCK_BOOTTIME_ALARM, CLOCK_MONOTONIC, CLOCK_MONOTONIC_COARSE, CLOCK_MONOTONIC_RAW,
-    CLOCK_PROCESS_CPUTIME_ID, CLOCK_REALTIME, CLOCK_REALTIME_ALARM, CLOCK_REALTIME_COARSE,
-    CLOCK_TAI, CLOCK_THREAD_CPUTIME_ID, TIMER_ABSTIME,
+    adjtimex, clock_getres, clock_gettime, clock_nanosleep, clock_settime, clockid_t, TimeSpec,
+    Timex, CLOCK_BOOTTIME, CLOCK_BOOTTIME_ALARM, CLOCK_MONOTONIC, CLOCK_MONOTONIC_COARSE,
+    CLOCK_MONOTONIC_RAW, CLOCK_PROCESS_CPUTIME_ID, CLOCK_REALTIME, CLOCK_REALTIME_ALARM,
+    CLOCK_REALTIME_COARSE, CLOCK_TAI, CLOCK_THREAD_CPUTIME_ID, TIMER_ABSTIME,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -122,6 +124,32 @@ impl ClockId {
             _ => None,
         }
     }
+
+    /// Returns `true` if the clock can be set with `set_time()`. On Linux this is
+    /// only `ClockRealtime`; `ClockTai` follows it, offset by the TAI offset set
+    /// with adjtimex(2).
+    pub const fn is_settable(&self) -> bool {
+        matches!(self, ClockId::ClockRealtime)
+    }
+
+    /// Returns `true` for the clocks measuring consumed CPU time,
+    /// `ClockProcessCputimeId` and `ClockThreadCputimeId`.
+    pub const fn is_cpu_clock(&self) -> bool {
+        matches!(
+            self,
+            ClockId::ClockProcessCputimeId | ClockId::ClockThreadCputimeId
+        )
+    }
+
+    /// Returns the current time of the clock. Same as `get_time()`.
+    pub fn now(&self) -> Result<TimeSpec, Errno> {
+        get_time(*self)
+    }
+
+    /// Returns the resolution of the clock. Same as `get_resolution()`.
+    pub fn resolution(&self) -> Result<TimeSpec, Errno> {
+        get_resolution(*self)
+    }
 }
 
 pub fn get_time(clockid: ClockId) -> Result<TimeSpec, Errno> {
@@ -129,6 +157,51 @@ pub fn get_time(clockid: ClockId) -> Result<TimeSpec, Errno> {
     unsafe { clock_gettime(clockid.as_raw(), &mut tp).and(Ok(tp)) }
 }
 
+/// CPU time consumed by the calling thread. Same as
+/// `get_time(ClockId::ClockThreadCputimeId)`.
+#[inline]
+pub fn get_thread_cpu_time() -> Result<TimeSpec, Errno> {
+    get_time(ClockId::ClockThreadCputimeId)
+}
+
+/// CPU time consumed by all threads of the calling process. Same as
+/// `get_time(ClockId::ClockProcessCputimeId)`.
+#[inline]
+pub fn get_process_cpu_time() -> Result<TimeSpec, Errno> {
+    get_time(ClockId::ClockProcessCputimeId)
+}
+
+/// Returns the resolution (precision) of `clockid`, as reported by
+/// `clock_getres()`.
+pub fn get_resolution(clockid: ClockId) -> Result<TimeSpec, Errno> {
+    let mut res = TimeSpec::zeroed();
+    unsafe { clock_getres(clockid.as_raw(), &mut res).and(Ok(res)) }
+}
+
+/// Memoizes the results of `get_resolution()`. The resolution of a clock does
+/// not change while the system runs, so each clock is queried only once.
+#[derive(Debug, Clone, Default)]
+pub struct TimerResolutionCache {
+    resolutions: HashMap<clockid_t, TimeSpec>,
+}
+
+impl TimerResolutionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached resolution of `clockid`, calling `clock_getres()` on
+    /// first use. Errors are not cached.
+    pub fn get_or_query(&mut self, clockid: ClockId) -> Result<TimeSpec, Errno> {
+        if let Some(res) = self.resolutions.get(&clockid.as_raw()) {
+            return Ok(*res);
+        }
+        let res = get_resolution(clockid)?;
+        self.resolutions.insert(clockid.as_raw(), res);
+        Ok(res)
+    }
+}
+
 pub fn set_time(clockid: ClockId, tp: TimeSpec) -> Result<(), Errno> {
     unsafe { clock_settime(clockid.as_raw(), &tp).and(Ok(())) }
 }
@@ -142,6 +215,163 @@ pub fn nanosleep_absolute(clockid: ClockId, tp: TimeSpec) -> Result<(), Errno> {
     }
 }
 
+/// Sleeps for the full `duration`, even if the sleep is interrupted by signals:
+/// on `EINTR` the sleep is restarted with the remaining time. Returns how much
+/// longer than `duration` the sleep actually took.
+pub fn sleep_interruptible(clockid: ClockId, duration: TimeSpec) -> Result<TimeSpec, Errno> {
+    let start = get_time(clockid)?;
+    let mut request = duration;
+    loop {
+        let mut remaining = TimeSpec::zeroed();
+        match unsafe { clock_nanosleep(clockid.as_raw(), 0, &request, &mut remaining) } {
+            Ok(_) => break,
+            Err(Errno::EINTR) => request = remaining,
+            Err(err) => return Err(err),
+        }
+    }
+    let extra = get_time(clockid)? - start - duration;
+    if extra.as_nanoseconds() > 0 {
+        Ok(extra)
+    } else {
+        Ok(TimeSpec::zeroed())
+    }
+}
+
+/// Sleeps until `clockid` reaches `absolute_time`. Same as `nanosleep_absolute()`.
+pub fn nanosleep_until(clockid: ClockId, absolute_time: TimeSpec) -> Result<(), Errno> {
+    nanosleep_absolute(clockid, absolute_time)
+}
+
+/// Sleeps for at least `offset` from now. Unlike `nanosleep_relative()`, the wakeup
+/// time is computed once and the sleep is restarted towards it on `EINTR`, so
+/// interruptions by signals do not make it drift.
+pub fn sleep_until_after(clockid: ClockId, offset: TimeSpec) -> Result<(), Errno> {
+    let wakeup = get_time(clockid)? + offset;
+    loop {
+        match nanosleep_absolute(clockid, wakeup) {
+            Err(Errno::EINTR) => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Busy-waits until `ClockMonotonic` reaches `absolute_time`.
+///
+/// This keeps the CPU at 100% for the whole wait and should only be used for very
+/// short waits (< 10 µs), where the wakeup latency of `clock_nanosleep()` is too
+/// large.
+pub fn spin_wait_until(absolute_time: TimeSpec) -> Result<(), Errno> {
+    spin_until(ClockId::ClockMonotonic, absolute_time)
+}
+
+/// A `spin_ns` for `nanosleep_relative_spinning()` that covers the wakeup
+/// latency of `clock_nanosleep()` on most systems.
+pub const DEFAULT_SPIN_NS: u64 = 10_000;
+
+/// Sleeps for `duration`, busy-waiting for the last `spin_ns` instead of
+/// sleeping, e.g. `DEFAULT_SPIN_NS`.
+///
+/// The end of the sleep is computed once up front, so the wakeup latency of the
+/// sleeping part is absorbed by the spinning part, see `hybrid_sleep()`.
+pub fn nanosleep_relative_spinning(
+    clockid: ClockId,
+    duration: TimeSpec,
+    spin_ns: u64,
+) -> Result<(), Errno> {
+    hybrid_sleep(clockid, get_time(clockid)? + duration, spin_ns)
+}
+
+/// Sleeps until `spin_threshold_ns` before `target` with `nanosleep_absolute()`
+/// and busy-waits for the rest. This achieves the precision of
+/// `spin_wait_until()` while only spinning for a short time at the end.
+pub fn hybrid_sleep(
+    clockid: ClockId,
+    target: TimeSpec,
+    spin_threshold_ns: u64,
+) -> Result<(), Errno> {
+    let spin_threshold = TimeSpec::nanoseconds(spin_threshold_ns.min(i64::MAX as u64) as i64);
+    let wakeup = target - spin_threshold;
+    if wakeup.as_nanoseconds() > get_time(clockid)?.as_nanoseconds() {
+        nanosleep_absolute(clockid, wakeup)?;
+    }
+    spin_until(clockid, target)
+}
+
+fn spin_until(clockid: ClockId, absolute_time: TimeSpec) -> Result<(), Errno> {
+    let target = absolute_time.as_nanoseconds();
+    while get_time(clockid)?.as_nanoseconds() < target {
+        core::hint::spin_loop();
+    }
+    Ok(())
+}
+
+const CLOCKSOURCE_DIR: &str = "/sys/devices/system/clocksource/clocksource0";
+
+/// Returns the name of the clock source the kernel currently uses for
+/// timekeeping, e.g. `tsc`.
+pub fn get_clocksource() -> Result<String, io::Error> {
+    let source = fs::read_to_string(format!("{CLOCKSOURCE_DIR}/current_clocksource"))?;
+    Ok(source.trim().to_string())
+}
+
+/// Returns the names of all clock sources the kernel can switch to.
+pub fn get_available_clocksources() -> Result<Vec<String>, io::Error> {
+    let sources = fs::read_to_string(format!("{CLOCKSOURCE_DIR}/available_clocksource"))?;
+    Ok(sources.split_whitespace().map(String::from).collect())
+}
+
+/// Clock sources that cannot be read from user space, so that the vDSO falls
+/// back to the `clock_gettime()` system call.
+const NON_VDSO_CLOCKSOURCES: [&str; 4] = ["jiffies", "refined-jiffies", "acpi_pm", "hpet"];
+
+/// Returns whether `get_time(clockid)` is served by the vDSO without entering the
+/// kernel.
+///
+/// This is the case if a vDSO is mapped into the process (see `/proc/self/maps`)
+/// and the clock is handled by it. The CPU-time and alarm clocks always use the
+/// system call. The coarse clocks only read the last tick, while all other clocks
+/// additionally need a clock source that can be read from user space, see
+/// `get_clocksource()`.
+///
+/// Note that this crate calls `clock_gettime()` through `syscall`, so the vDSO
+/// is only used by callers going through the C library.
+pub fn clock_is_vdso_accelerated(clockid: ClockId) -> bool {
+    let vdso_mapped = fs::read_to_string("/proc/self/maps")
+        .map(|maps| maps.lines().any(|line| line.ends_with("[vdso]")))
+        .unwrap_or(false);
+    if !vdso_mapped {
+        return false;
+    }
+    match clockid {
+        ClockId::ClockRealtimeCoarse | ClockId::ClockMonotonicCoarse => true,
+        ClockId::ClockRealtime
+        | ClockId::ClockTai
+        | ClockId::ClockMonotonic
+        | ClockId::ClockMonotonicRaw
+        | ClockId::ClockBoottime => get_clocksource()
+            .map(|source| !NON_VDSO_CLOCKSOURCES.contains(&source.as_str()))
+            .unwrap_or(false),
+        ClockId::ClockRealtimeAlarm
+        | ClockId::ClockBoottimeAlarm
+        | ClockId::ClockProcessCputimeId
+        | ClockId::ClockThreadCputimeId => false,
+    }
+}
+
+/// Reads the state of the kernel clock discipline, e.g. the estimated clock error
+/// and the TAI-UTC offset, without changing it.
+pub fn get_timex() -> Result<Timex, Errno> {
+    let mut tx = Timex::default();
+    unsafe { adjtimex(&mut tx).and(Ok(tx)) }
+}
+
+/// Returns the current offset between `ClockTai` and `ClockRealtime` in seconds,
+/// as known to the kernel. It stays 0 until it is set, e.g. by an NTP or PTP
+/// daemon, in which case `ClockTai` timestamps are identical to UTC.
+pub fn get_tai_offset() -> Result<i32, Errno> {
+    get_timex().map(|tx| tx.tai)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -153,9 +383,133 @@ mod tests {
         assert!(time.tv_sec > 0);
     }
 
+    #[test]
+    fn test_clock_kinds() {
+        assert!(ClockId::ClockRealtime.is_settable());
+        assert!(!ClockId::ClockTai.is_settable());
+        assert!(!ClockId::ClockMonotonic.is_settable());
+        let now = get_time(ClockId::ClockMonotonic).unwrap();
+        assert_eq!(set_time(ClockId::ClockMonotonic, now), Err(Errno::EINVAL));
+
+        assert!(ClockId::ClockThreadCputimeId.is_cpu_clock());
+        assert!(ClockId::ClockProcessCputimeId.is_cpu_clock());
+        assert!(!ClockId::ClockBoottime.is_cpu_clock());
+    }
+
+    #[test]
+    fn test_resolution_cache() {
+        let mut cache = TimerResolutionCache::new();
+        let res = cache.get_or_query(ClockId::ClockMonotonic).unwrap();
+        assert_eq!(res, get_resolution(ClockId::ClockMonotonic).unwrap());
+        assert_eq!(cache.get_or_query(ClockId::ClockMonotonic).unwrap(), res);
+        assert_eq!(cache.resolutions.len(), 1);
+    }
+
+    #[test]
+    fn test_clocksource() {
+        let current = get_clocksource().unwrap();
+        assert!(!current.is_empty());
+        assert!(get_available_clocksources().unwrap().contains(&current));
+    }
+
+    #[test]
+    fn test_cpu_time() {
+        let thread = get_thread_cpu_time().unwrap();
+        let process = get_process_cpu_time().unwrap();
+        assert!(thread.as_nanoseconds() > 0);
+        assert!(process.as_nanoseconds() >= thread.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_resolution() {
+        let res = ClockId::ClockMonotonic.resolution().unwrap();
+        assert!(res.as_nanoseconds() > 0);
+        assert_eq!(
+            get_resolution(ClockId::ClockMonotonic)
+                .unwrap()
+                .as_nanoseconds(),
+            res.as_nanoseconds()
+        );
+        let coarse = ClockId::ClockMonotonicCoarse.resolution().unwrap();
+        assert!(coarse.as_nanoseconds() >= res.as_nanoseconds());
+        let before = get_time(ClockId::ClockMonotonic).unwrap();
+        let now = ClockId::ClockMonotonic.now().unwrap();
+        assert!(now.as_nanoseconds() >= before.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_vdso_accelerated() {
+        assert!(!clock_is_vdso_accelerated(ClockId::ClockThreadCputimeId));
+        assert!(!clock_is_vdso_accelerated(ClockId::ClockBoottimeAlarm));
+        if clock_is_vdso_accelerated(ClockId::ClockMonotonic) {
+            assert!(clock_is_vdso_accelerated(ClockId::ClockMonotonicCoarse));
+        }
+    }
+
+    #[test]
+    fn test_timex() {
+        let tx = get_timex().unwrap();
+        assert_eq!(tx.modes, 0);
+    }
+
+    #[test]
+    fn test_tai_offset() {
+        let offset = get_tai_offset().unwrap();
+        assert!(offset >= 0);
+    }
+
+    #[test]
+    fn test_sleep_until() {
+        let start = get_time(ClockId::ClockMonotonic).unwrap();
+        let offset = TimeSpec::nanoseconds(1_000_000);
+        nanosleep_until(ClockId::ClockMonotonic, start + offset).unwrap();
+        let after = get_time(ClockId::ClockMonotonic).unwrap();
+        assert!((after - start).as_nanoseconds() >= offset.as_nanoseconds());
+
+        sleep_until_after(ClockId::ClockMonotonic, offset).unwrap();
+        let elapsed = get_time(ClockId::ClockMonotonic).unwrap() - after;
+        assert!(elapsed.as_nanoseconds() >= offset.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_sleep_interruptible() {
+        let duration = TimeSpec::nanoseconds(1_000_000);
+        let start = get_time(ClockId::ClockMonotonic).unwrap();
+        let extra = sleep_interruptible(ClockId::ClockMonotonic, duration).unwrap();
+        let elapsed = get_time(ClockId::ClockMonotonic).unwrap() - start;
+        assert!(extra.as_nanoseconds() >= 0);
+        assert!(elapsed.as_nanoseconds() >= duration.as_nanoseconds() + extra.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_spin_wait() {
+        let target = get_time(ClockId::ClockMonotonic).unwrap() + TimeSpec::nanoseconds(5_000);
+        spin_wait_until(target).unwrap();
+        let now = get_time(ClockId::ClockMonotonic).unwrap();
+        assert!(now.as_nanoseconds() >= target.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_hybrid_sleep() {
+        let target = get_time(ClockId::ClockMonotonic).unwrap() + TimeSpec::nanoseconds(2_000_000);
+        hybrid_sleep(ClockId::ClockMonotonic, target, 50_000).unwrap();
+        let now = get_time(ClockId::ClockMonotonic).unwrap();
+        assert!(now.as_nanoseconds() >= target.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_nanosleep_relative_spinning() {
+        let start = get_time(ClockId::ClockMonotonic).unwrap();
+        let duration = TimeSpec::nanoseconds(1_000_000);
+        nanosleep_relative_spinning(ClockId::ClockMonotonic, duration, DEFAULT_SPIN_NS).unwrap();
+        let elapsed = get_time(ClockId::ClockMonotonic).unwrap() - start;
+        assert!(elapsed.as_nanoseconds() >= duration.as_nanoseconds());
+    }
+
     #[test]
     fn test_sleep() {
-        let time = nanosleep_relative(
+        let start = get_time(ClockId::ClockMonotonic).unwrap();
+        nanosleep_relative(
             ClockId::ClockMonotonic,
             TimeSpec {
                 tv_sec: 0,
@@ -163,6 +517,7 @@ mod tests {
             },
         )
         .unwrap();
-        // assert!(time.tv_sec > 0);
+        let elapsed = get_time(ClockId::ClockMonotonic).unwrap() - start;
+        assert!(elapsed.as_nanoseconds() >= 1_000_000);
     }
 }
@@ -0,0 +1,284 @@
+use std::{error, fmt, str::FromStr};
+
+use crate::CpuSet;
+
+/// Error returned if a textual CPU list or mask cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCpuSetError {
+    /// A CPU index or range is not a valid number.
+    InvalidNumber(String),
+    /// The start of a range is larger than its end, or its stride is zero.
+    InvalidRange(String),
+    /// The CPU index cannot be represented in a `CpuSet`.
+    OutOfRange(usize),
+}
+
+impl fmt::Display for ParseCpuSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCpuSetError::InvalidNumber(s) => write!(f, "invalid CPU number `{s}`"),
+            ParseCpuSetError::InvalidRange(s) => write!(f, "invalid CPU range `{s}`"),
+            ParseCpuSetError::OutOfRange(cpu) => {
+                write!(f, "CPU {cpu} is out of range for a CpuSet")
+            }
+        }
+    }
+}
+
+impl error::Error for ParseCpuSetError {}
+
+fn parse_cpu(s: &str) -> Result<usize, ParseCpuSetError> {
+    let cpu = s
+        .trim()
+        .parse()
+        .map_err(|_| ParseCpuSetError::InvalidNumber(s.to_string()))?;
+    if cpu >= CpuSet::n_cpus() {
+        return Err(ParseCpuSetError::OutOfRange(cpu));
+    }
+    Ok(cpu)
+}
+
+/// Parses a hexadecimal CPU mask as shown by taskset(1) or used by sysfs and
+/// procfs, e.g. `ff0f` or `00000000,0000ff0f`.
+///
+/// The mask is read as one big-endian number: the rightmost digit holds CPUs
+/// 0-3, with CPU 0 in its least significant bit, so `ff0f` contains CPUs 0-3
+/// and 8-15. Commas and a `0x` prefix are ignored.
+pub fn sched_affinity_mask_from_hex(s: &str) -> Result<CpuSet, ParseCpuSetError> {
+    let trimmed = s.trim();
+    let digits = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    let mut set = CpuSet::empty();
+    for (idx, c) in digits.chars().filter(|&c| c != ',').rev().enumerate() {
+        let nibble = c
+            .to_digit(16)
+            .ok_or_else(|| ParseCpuSetError::InvalidNumber(s.to_string()))?;
+        for bit in 0..4 {
+            if nibble & (1 << bit) != 0 {
+                let cpu = idx * 4 + bit;
+                if cpu >= CpuSet::n_cpus() {
+                    return Err(ParseCpuSetError::OutOfRange(cpu));
+                }
+                set = set.set(cpu);
+            }
+        }
+    }
+    Ok(set)
+}
+
+/// Parses the value of the `Cpus_allowed_list:` field of `/proc/[pid]/status`,
+/// e.g. `0-3,8`. An empty list, as shown if none of the allowed CPUs is online,
+/// results in an empty set.
+pub fn sched_affinity_cpulist_to_cpuset(cpulist: &str) -> Result<CpuSet, ParseCpuSetError> {
+    cpulist.parse()
+}
+
+/// Parsers for the affinity fields of `/proc/[pid]/status`, which show the
+/// same mask once as a CPU list and once in hexadecimal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuSetParser;
+
+impl CpuSetParser {
+    /// Parses the value of `Cpus_allowed_list:`, see
+    /// `sched_affinity_cpulist_to_cpuset()`.
+    pub fn cpus_allowed_list(value: &str) -> Result<CpuSet, ParseCpuSetError> {
+        sched_affinity_cpulist_to_cpuset(value)
+    }
+
+    /// Parses the value of `Cpus_allowed:`, a hexadecimal mask in 32-bit
+    /// groups separated by commas, see `sched_affinity_mask_from_hex()`.
+    pub fn cpus_allowed(value: &str) -> Result<CpuSet, ParseCpuSetError> {
+        sched_affinity_mask_from_hex(value)
+    }
+}
+
+/// Parses a CPU list as used by taskset(1) and the kernel, e.g. `0-3,8,10-14:2`:
+/// comma separated CPU indices and inclusive ranges, optionally with a stride.
+/// An empty string results in an empty set.
+impl FromStr for CpuSet {
+    type Err = ParseCpuSetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = CpuSet::empty();
+        for item in s.trim().split(',').filter(|item| !item.trim().is_empty()) {
+            let (range, stride) = match item.split_once(':') {
+                Some((range, stride)) => (range, parse_cpu(stride)?),
+                None => (item, 1),
+            };
+            let (first, last) = match range.split_once('-') {
+                Some((first, last)) => (parse_cpu(first)?, parse_cpu(last)?),
+                None => {
+                    let cpu = parse_cpu(range)?;
+                    (cpu, cpu)
+                }
+            };
+            if first > last || stride == 0 {
+                return Err(ParseCpuSetError::InvalidRange(item.to_string()));
+            }
+            for cpu in (first..=last).step_by(stride) {
+                set = set.set(cpu);
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl CpuSet {
+    /// Formats the set as a CPU list in the format accepted by `from_str()`,
+    /// e.g. `0-3,8`. Consecutive CPUs are merged into ranges.
+    pub fn to_range_string(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for cpu in self.iter() {
+            match ranges.last_mut() {
+                Some((_, last)) if *last + 1 == cpu => *last = cpu,
+                _ => ranges.push((cpu, cpu)),
+            }
+        }
+        ranges
+            .iter()
+            .map(|&(first, last)| {
+                if first == last {
+                    first.to_string()
+                } else {
+                    format!("{first}-{last}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Picks a random CPU from the set, e.g. for load balancing. `random_fn(max)`
+    /// must return a random number in `[0, max)`; it is called once with the
+    /// number of CPUs in the set. Returns `None` if the set is empty.
+    ///
+    /// With the `rand` crate: `set.sample_with(|max| rng.gen_range(0..max))`.
+    pub fn sample_with<F: Fn(usize) -> usize>(&self, random_fn: F) -> Option<usize> {
+        match self.count() {
+            0 => None,
+            count => self.iter().nth(random_fn(count)),
+        }
+    }
+
+    /// Formats the set as a hexadecimal mask in the format accepted by
+    /// [`sched_affinity_mask_from_hex()`], e.g. `ff0f` for CPUs 0-3 and 8-15.
+    /// Leading zeros are omitted; an empty set is formatted as `0`.
+    pub fn to_hex_string(&self) -> String {
+        let Some(last) = self.iter().last() else {
+            return "0".to_string();
+        };
+        (0..=last / 4)
+            .rev()
+            .map(|nibble| {
+                let value = (0..4)
+                    .filter(|bit| self.is_set(nibble * 4 + bit))
+                    .fold(0, |acc, bit| acc | (1 << bit));
+                char::from_digit(value, 16).unwrap()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        let set: CpuSet = "0-3,8, 10-14:2".parse().unwrap();
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 8, 10, 12, 14]
+        );
+        assert_eq!("".parse::<CpuSet>().unwrap(), CpuSet::empty());
+        assert_eq!("5\n".parse::<CpuSet>().unwrap(), CpuSet::empty().set(5));
+
+        assert_eq!(
+            "1,a".parse::<CpuSet>(),
+            Err(ParseCpuSetError::InvalidNumber("a".to_string()))
+        );
+        assert_eq!(
+            "3-1".parse::<CpuSet>(),
+            Err(ParseCpuSetError::InvalidRange("3-1".to_string()))
+        );
+        assert_eq!(
+            "0-3:0".parse::<CpuSet>(),
+            Err(ParseCpuSetError::InvalidRange("0-3:0".to_string()))
+        );
+        assert_eq!(
+            "100000".parse::<CpuSet>(),
+            Err(ParseCpuSetError::OutOfRange(100000))
+        );
+    }
+
+    #[test]
+    fn test_hex_mask() {
+        let set = sched_affinity_mask_from_hex("ff0f").unwrap();
+        assert_eq!(set.to_range_string(), "0-3,8-15");
+        let set = sched_affinity_mask_from_hex("00000001,00000000\n").unwrap();
+        assert_eq!(set.to_range_string(), "32");
+        assert_eq!(
+            sched_affinity_mask_from_hex("0x3")
+                .unwrap()
+                .to_range_string(),
+            "0-1"
+        );
+        assert!(sched_affinity_mask_from_hex("fg").is_err());
+    }
+
+    #[test]
+    fn test_cpu_set_parser() {
+        assert_eq!(
+            sched_affinity_cpulist_to_cpuset("0-3,8\n").unwrap(),
+            CpuSet::empty().set(0).set(1).set(2).set(3).set(8)
+        );
+        assert_eq!(
+            CpuSetParser::cpus_allowed_list("").unwrap(),
+            CpuSet::empty()
+        );
+        assert_eq!(
+            CpuSetParser::cpus_allowed("00000000,0000010f").unwrap(),
+            CpuSetParser::cpus_allowed_list("0-3,8").unwrap()
+        );
+
+        let status = std::fs::read_to_string(crate::Pid::this().proc_path("status")).unwrap();
+        let field = |name| crate::stats::status_field(&status, name).unwrap();
+        let list = CpuSetParser::cpus_allowed_list(field("Cpus_allowed_list")).unwrap();
+        let mask = CpuSetParser::cpus_allowed(field("Cpus_allowed")).unwrap();
+        assert_eq!(list, mask);
+        assert_eq!(list, crate::get_affinity(crate::Pid::this()).unwrap());
+    }
+
+    #[test]
+    fn test_to_hex_string() {
+        assert_eq!(CpuSet::empty().to_hex_string(), "0");
+        let set = sched_affinity_mask_from_hex("ff0f").unwrap();
+        assert_eq!(set.to_hex_string(), "ff0f");
+        assert_eq!(CpuSet::empty().set(32).to_hex_string(), "100000000");
+        assert_eq!(
+            sched_affinity_mask_from_hex(&set.to_hex_string()).unwrap(),
+            set
+        );
+    }
+
+    #[test]
+    fn test_sample_with() {
+        assert_eq!(CpuSet::empty().sample_with(|_| 0), None);
+        let set: CpuSet = "2,5,9".parse().unwrap();
+        assert_eq!(set.sample_with(|_| 0), Some(2));
+        assert_eq!(set.sample_with(|_| 2), Some(9));
+        assert_eq!(set.sample_with(|max| max), None);
+        let max = std::cell::Cell::new(0);
+        set.sample_with(|m| {
+            max.set(m);
+            0
+        });
+        assert_eq!(max.get(), 3);
+    }
+
+    #[test]
+    fn test_to_range_string() {
+        assert_eq!(CpuSet::empty().to_range_string(), "");
+        let set = CpuSet::empty().set(0).set(1).set(2).set(3).set(8).set(10);
+        assert_eq!(set.to_range_string(), "0-3,8,10");
+        assert_eq!(set.to_range_string().parse::<CpuSet>().unwrap(), set);
+    }
+}
@@ -0,0 +1,95 @@
+use std::cell::Cell;
+
+use syscalls::Errno;
+
+use crate::{get_thread_cpu_time, sched_yield, set_attr, Attributes, Pid, SchedError};
+
+/// Runs the calling thread as a `SCHED_DEADLINE` task with the canonical loop:
+/// do the work of one activation, then `sched_yield()` to give up the remaining
+/// runtime until the next period.
+///
+/// The thread is configured with `SCHED_FLAG_DL_OVERRUN`, so the kernel sends
+/// `SIGXCPU` to the process whenever an activation exceeds its runtime. The
+/// default action of `SIGXCPU` terminates the process, so handle or ignore the
+/// signal before creating the task. Independently of the signal, `run()` counts
+/// the activations that consumed more CPU time than the runtime, see
+/// `overruns()`.
+#[derive(Debug)]
+pub struct DeadlineTask {
+    runtime_ns: u64,
+    overruns: Cell<u64>,
+    stopped: Cell<bool>,
+}
+
+impl DeadlineTask {
+    /// Switches the calling thread to `SCHED_DEADLINE` with the given parameters
+    /// in nanoseconds, see `Attributes::for_deadline()`.
+    pub fn new(runtime_ns: u64, deadline_ns: u64, period_ns: u64) -> Result<Self, SchedError> {
+        let attr = Attributes::for_deadline(runtime_ns, deadline_ns, period_ns)?.with_dl_overrun();
+        set_attr(Pid::this(), attr)?;
+        Ok(Self {
+            runtime_ns,
+            overruns: Cell::new(0),
+            stopped: Cell::new(false),
+        })
+    }
+
+    /// Calls `body` once per period until `stop()` is called.
+    ///
+    /// Must be called on the thread that created the task.
+    pub fn run<F: FnMut()>(&self, mut body: F) -> Result<(), Errno> {
+        self.stopped.set(false);
+        while !self.stopped.get() {
+            let start = get_thread_cpu_time()?;
+            body();
+            let used = get_thread_cpu_time()?.as_nanoseconds() - start.as_nanoseconds();
+            if used > self.runtime_ns as i64 {
+                self.overruns.set(self.overruns.get() + 1);
+            }
+            sched_yield()?;
+        }
+        Ok(())
+    }
+
+    /// Makes `run()` return after the current activation.
+    pub fn stop(&self) {
+        self.stopped.set(true);
+    }
+
+    /// Number of activations that needed more CPU time than the runtime.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_attr, sched::in_deadline_thread, Policy, SchedFlags};
+
+    #[test]
+    fn test_deadline_task() {
+        let (attr, activations, overruns) = in_deadline_thread(|| {
+            let task = DeadlineTask::new(2_000_000, 10_000_000, 10_000_000).unwrap();
+            let attr = get_attr(Pid::this()).unwrap();
+            let mut activations = 0;
+            task.run(|| {
+                activations += 1;
+                if activations == 3 {
+                    task.stop();
+                }
+            })
+            .unwrap();
+            (attr, activations, task.overruns())
+        });
+        assert_eq!(attr.policy, Policy::Deadline);
+        assert!(attr.flags.contains(SchedFlags::SCHED_FLAG_DL_OVERRUN));
+        assert_eq!(activations, 3);
+        assert_eq!(overruns, 0);
+
+        assert!(matches!(
+            DeadlineTask::new(10, 5, 5),
+            Err(SchedError::InvalidAttributes(_))
+        ));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{error, fmt, io};
+
+use syscalls::Errno;
+
+use crate::{ParseCpuSetError, PriorityRange};
+
+/// Errors returned by the higher level scheduling helpers.
+#[derive(Debug)]
+pub enum SchedError {
+    /// A system call failed.
+    Errno(Errno),
+    /// Reading or writing a file in procfs or sysfs failed.
+    Io(io::Error),
+    /// The requested deadline parameters need more CPU bandwidth than the
+    /// system reserves for real-time tasks.
+    BandwidthExceeded,
+    /// A priority is outside the range that is valid for the policy.
+    PriorityOutOfRange { priority: u32, range: PriorityRange },
+    /// The scheduling attributes are inconsistent, e.g. a deadline runtime that
+    /// is larger than the deadline. The message describes the problem.
+    InvalidAttributes(&'static str),
+    /// `PolicyStack::pop()` was called without a matching `push()`.
+    EmptyPolicyStack,
+    /// `PolicyNegotiator::try_policies()` was called without any candidates.
+    NoCandidates,
+    /// A textual CPU list or mask could not be parsed.
+    ParseCpuSet(ParseCpuSetError),
+    /// A raw policy value does not correspond to any `Policy`.
+    UnknownPolicy(u32),
+    /// A fraction passed to `priority_for_fraction()` is not within
+    /// `0.0..=1.0`.
+    FractionOutOfRange(f64),
+}
+
+impl fmt::Display for SchedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedError::Errno(errno) => write!(f, "system call failed: {errno}"),
+            SchedError::Io(err) => write!(f, "I/O error: {err}"),
+            SchedError::BandwidthExceeded => {
+                write!(
+                    f,
+                    "not enough real-time bandwidth for the deadline parameters"
+                )
+            }
+            SchedError::PriorityOutOfRange { priority, range } => write!(
+                f,
+                "priority {priority} is outside the valid range {}..={}",
+                range.min, range.max
+            ),
+            SchedError::InvalidAttributes(msg) => write!(f, "invalid attributes: {msg}"),
+            SchedError::EmptyPolicyStack => write!(f, "no saved scheduling policy to restore"),
+            SchedError::NoCandidates => write!(f, "no candidate scheduling attributes to try"),
+            SchedError::ParseCpuSet(err) => write!(f, "invalid CPU set: {err}"),
+            SchedError::UnknownPolicy(raw) => write!(f, "unknown scheduling policy {raw}"),
+            SchedError::FractionOutOfRange(fraction) => {
+                write!(f, "fraction {fraction} is outside the range 0.0..=1.0")
+            }
+        }
+    }
+}
+
+impl error::Error for SchedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SchedError::Io(err) => Some(err),
+            SchedError::ParseCpuSet(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Errno> for SchedError {
+    fn from(errno: Errno) -> Self {
+        SchedError::Errno(errno)
+    }
+}
+
+impl From<io::Error> for SchedError {
+    fn from(err: io::Error) -> Self {
+        SchedError::Io(err)
+    }
+}
+
+impl From<ParseCpuSetError> for SchedError {
+    fn from(err: ParseCpuSetError) -> Self {
+        SchedError::ParseCpuSet(err)
+    }
+}
+
+/// Allows returning a `SchedError` from functions that report `io::Error`.
+impl From<SchedError> for io::Error {
+    fn from(err: SchedError) -> Self {
+        match err {
+            SchedError::Errno(errno) => io::Error::from_raw_os_error(errno.into_raw()),
+            SchedError::Io(err) => err,
+            err => io::Error::other(err),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+/// Parameters of a `SCHED_DEADLINE` task relevant for admission control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdfTaskSpec {
+    pub runtime_ns: u64,
+    pub period_ns: u64,
+}
+
+/// EDF utilization bound test: returns `true` if the sum of `runtime_ns / period_ns`
+/// over all tasks does not exceed 1, i.e. if the tasks are schedulable on a single
+/// CPU. The sum is computed exactly, without floating point rounding.
+pub fn edf_feasible(tasks: &[EdfTaskSpec]) -> bool {
+    utilization_sum(tasks.iter().map(|task| (task.runtime_ns, task.period_ns)))
+        .is_some_and(|(num, den)| num <= den)
+}
+
+/// Liu & Layland test for rate-monotonic priority assignment of `SCHED_FIFO` or
+/// `SCHED_RR` tasks, given as `(runtime_ns, period_ns)` pairs: returns `true` if
+/// the total utilization does not exceed `n * (2^(1/n) - 1)` for `n` tasks.
+///
+/// The test is sufficient but not necessary, a task set failing it may still be
+/// schedulable.
+pub fn rm_feasible(utilizations: &[(u64, u64)]) -> bool {
+    if utilizations.is_empty() {
+        return true;
+    }
+    let n = utilizations.len() as f64;
+    let bound = n * (2f64.powf(1.0 / n) - 1.0);
+    utilization_sum(utilizations.iter().copied())
+        .is_some_and(|(num, den)| num as f64 / den as f64 <= bound)
+}
+
+/// Fraction of a CPU used by a task, `runtime_ns / period_ns`. Meant for display
+/// and quick estimates; use `edf_feasible()` for exact admission control.
+pub fn utilization(runtime_ns: u64, period_ns: u64) -> f64 {
+    runtime_ns as f64 / period_ns as f64
+}
+
+/// Slack between the end of the runtime and the deadline, as a fraction of the
+/// period: `(deadline_ns - runtime_ns) / period_ns`. Negative if the runtime
+/// exceeds the deadline.
+pub fn deadline_headroom(runtime_ns: u64, deadline_ns: u64, period_ns: u64) -> f64 {
+    (deadline_ns as f64 - runtime_ns as f64) / period_ns as f64
+}
+
+const FIXED_ONE: u128 = 1 << 64;
+
+const fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Sum of the utilizations `runtime / period` as fraction `(numerator, denominator)`.
+/// Returns `None` if a period is zero or the sum is too large to be represented.
+fn utilization_sum(tasks: impl Iterator<Item = (u64, u64)>) -> Option<(u128, u128)> {
+    let tasks: Vec<(u64, u64)> = tasks.collect();
+    if tasks.iter().any(|&(_, period)| period == 0) {
+        return None;
+    }
+    exact_sum(&tasks).or_else(|| fixed_point_sum(&tasks))
+}
+
+fn exact_sum(tasks: &[(u64, u64)]) -> Option<(u128, u128)> {
+    tasks
+        .iter()
+        .try_fold((0u128, 1u128), |(num, den), &(runtime, period)| {
+            let (runtime, period) = (runtime as u128, period as u128);
+            let lcm = (den / gcd(den, period)).checked_mul(period)?;
+            let num = num
+                .checked_mul(lcm / den)?
+                .checked_add(runtime.checked_mul(lcm / period)?)?;
+            let div = gcd(num, lcm);
+            Some((num / div, lcm / div))
+        })
+}
+
+/// Fallback if the common denominator of the periods overflows. Every utilization
+/// is rounded up, so the sum is never underestimated.
+fn fixed_point_sum(tasks: &[(u64, u64)]) -> Option<(u128, u128)> {
+    tasks
+        .iter()
+        .try_fold(0u128, |sum, &(runtime, period)| {
+            sum.checked_add(((runtime as u128) << 64).div_ceil(period as u128))
+        })
+        .map(|num| (num, FIXED_ONE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(runtime_ns: u64, period_ns: u64) -> EdfTaskSpec {
+        EdfTaskSpec {
+            runtime_ns,
+            period_ns,
+        }
+    }
+
+    #[test]
+    fn test_edf_feasible() {
+        assert!(edf_feasible(&[]));
+        assert!(edf_feasible(&[task(1, 3), task(1, 3), task(1, 3)]));
+        assert!(edf_feasible(&[
+            task(500_000, 1_000_000),
+            task(5_000_000, 10_000_000)
+        ]));
+        assert!(!edf_feasible(&[
+            task(500_001, 1_000_000),
+            task(5_000_000, 10_000_000)
+        ]));
+        assert!(!edf_feasible(&[task(1, 0)]));
+        assert!(!edf_feasible(&[task(2, 1)]));
+    }
+
+    #[test]
+    fn test_rm_feasible() {
+        assert!(rm_feasible(&[]));
+        assert!(rm_feasible(&[(1_000_000, 1_000_000)]));
+        // bound for two tasks is ~0.828
+        assert!(rm_feasible(&[(400, 1_000), (400, 1_000)]));
+        assert!(!rm_feasible(&[(450, 1_000), (400, 1_000)]));
+        // feasible under EDF, but not guaranteed under RM
+        assert!(edf_feasible(&[task(450, 1_000), task(400, 1_000)]));
+        assert!(!rm_feasible(&[(1, 0)]));
+    }
+
+    #[test]
+    fn test_utilization() {
+        assert_eq!(utilization(500_000, 10_000_000), 0.05);
+        assert_eq!(utilization(0, 1_000), 0.0);
+        assert_eq!(deadline_headroom(500_000, 1_000_000, 10_000_000), 0.05);
+        assert_eq!(deadline_headroom(1_000, 1_000, 1_000), 0.0);
+        assert!(deadline_headroom(2_000, 1_000, 10_000) < 0.0);
+    }
+
+    #[test]
+    fn test_utilization_sum_overflow() {
+        // pairwise coprime periods whose product does not fit into 128 bits
+        let periods = [
+            1_000_000_007,
+            998_244_353,
+            1_000_000_009,
+            999_999_937,
+            2_147_483_647,
+        ];
+        assert!(exact_sum(&periods.map(|p| (1, p))).is_none());
+        let tasks = periods.map(|p| task(p / 10, p));
+        assert!(edf_feasible(&tasks));
+        let tasks = periods.map(|p| task(p / 4, p));
+        assert!(!edf_feasible(&tasks));
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    get_affinity, get_attr,
+    prctl::{get_timer_slack_of, set_timer_slack_of},
+    set_affinity, set_attr, Attributes, CpuSet, Pid, SchedError,
+};
+use syscalls::Errno;
+
+/// A stack of scheduling attributes of a thread. Each `push()` saves the current
+/// attributes before applying new ones, each `pop()` restores the attributes that
+/// were active before the matching `push()`.
+pub struct PolicyStack {
+    pid: Pid,
+    saved: Vec<Attributes>,
+}
+
+impl PolicyStack {
+    /// Creates an empty stack for the thread whose ID is specified in `pid`.
+    pub fn new(pid: Pid) -> Self {
+        Self {
+            pid,
+            saved: Vec::new(),
+        }
+    }
+
+    /// Saves the current attributes of the thread and applies `new_attr`.
+    pub fn push(&mut self, new_attr: Attributes) -> Result<(), SchedError> {
+        let current = get_attr(self.pid)?;
+        set_attr(self.pid, new_attr)?;
+        self.saved.push(current);
+        Ok(())
+    }
+
+    /// Restores the attributes saved by the last `push()`. Returns
+    /// `SchedError::EmptyPolicyStack` if there is nothing to restore.
+    pub fn pop(&mut self) -> Result<(), SchedError> {
+        let previous = self.saved.pop().ok_or(SchedError::EmptyPolicyStack)?;
+        if let Err(err) = set_attr(self.pid, previous.clone()) {
+            self.saved.push(previous);
+            return Err(SchedError::Errno(err));
+        }
+        Ok(())
+    }
+
+    /// Number of saved attributes.
+    pub fn depth(&self) -> usize {
+        self.saved.len()
+    }
+}
+
+/// The complete scheduling state of a thread: its attributes, CPU affinity and
+/// timer slack. Restores the state when dropped, unless `restore()` was called
+/// before.
+#[derive(Debug)]
+pub struct SchedulingSnapshot {
+    pid: Pid,
+    attr: Attributes,
+    affinity: CpuSet,
+    timer_slack: u64,
+    restored: Cell<bool>,
+}
+
+impl SchedulingSnapshot {
+    /// Captures the state of the thread whose ID is specified in `pid`. If `pid`
+    /// is zero, the state is restored on the thread that drops the snapshot.
+    pub fn capture(pid: Pid) -> Result<SchedulingSnapshot, SchedError> {
+        Ok(SchedulingSnapshot {
+            pid,
+            attr: get_attr(pid)?,
+            affinity: get_affinity(pid)?,
+            timer_slack: get_timer_slack_of(pid)?,
+            restored: Cell::new(false),
+        })
+    }
+
+    /// Restores the captured state. The affinity is restored first, so that a
+    /// `SCHED_DEADLINE` policy is applied with the affinity it was admitted with.
+    pub fn restore(&self) -> Result<(), SchedError> {
+        self.restored.set(true);
+        set_affinity(self.pid, self.affinity)?;
+        set_attr(self.pid, self.attr.clone())?;
+        set_timer_slack_of(self.pid, self.timer_slack)
+    }
+}
+
+impl Drop for SchedulingSnapshot {
+    fn drop(&mut self) {
+        if !self.restored.get() {
+            let _ = self.restore();
+        }
+    }
+}
+
+/// Applies the first of several scheduling attributes that the thread is allowed
+/// to use, so applications can degrade gracefully, e.g. from `SCHED_DEADLINE`
+/// to `SCHED_FIFO` to a nice value if they lack the privileges for real-time
+/// scheduling.
+pub struct PolicyNegotiator {
+    pid: Pid,
+}
+
+impl PolicyNegotiator {
+    /// Creates a negotiator for the thread whose ID is specified in `pid`.
+    pub fn new(pid: Pid) -> Self {
+        Self { pid }
+    }
+
+    /// Tries each of `candidates` in order with `set_attr()` and returns the
+    /// first one that was applied.
+    ///
+    /// Only `EPERM` moves on to the next candidate; any other error, e.g.
+    /// `EINVAL` for malformed attributes, is returned immediately. If all
+    /// candidates fail with `EPERM`, the error of the last one is returned.
+    /// Fails with `SchedError::NoCandidates` if `candidates` is empty.
+    pub fn try_policies(&self, candidates: &[Attributes]) -> Result<Attributes, SchedError> {
+        let mut last_err = SchedError::NoCandidates;
+        for attr in candidates {
+            match set_attr(self.pid, attr.clone()) {
+                Ok(()) => return Ok(attr.clone()),
+                Err(Errno::EPERM) => last_err = SchedError::Errno(Errno::EPERM),
+                Err(err) => return Err(SchedError::Errno(err)),
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// Runs `f` with the scheduling attributes `attr` applied to the thread whose ID
+/// is specified in `pid`, and restores the previous attributes afterwards.
+///
+/// The attributes are restored even if `f` panics; the panic is then resumed
+/// after restoring. If `attr` cannot be applied, `f` is not run.
+pub fn scoped_scheduling(pid: Pid, attr: Attributes, f: impl FnOnce()) -> Result<(), SchedError> {
+    let previous = get_attr(pid)?;
+    set_attr(pid, attr)?;
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    let restored = set_attr(pid, previous);
+    if let Err(payload) = result {
+        panic::resume_unwind(payload);
+    }
+    Ok(restored?)
+}
+
+/// Runs `f` with the CPU affinity `set` applied to the thread whose ID is
+/// specified in `pid`, and restores the previous affinity afterwards.
+///
+/// Like `scoped_scheduling()`, the affinity is restored even if `f` panics.
+pub fn scoped_affinity(pid: Pid, set: CpuSet, f: impl FnOnce()) -> Result<(), SchedError> {
+    let previous = get_affinity(pid)?;
+    set_affinity(pid, set)?;
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    let restored = set_affinity(pid, previous);
+    if let Err(payload) = result {
+        panic::resume_unwind(payload);
+    }
+    Ok(restored?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_fifo, Policy, SchedFlags};
+
+    #[test]
+    fn test_policy_stack() {
+        set_fifo(Pid::this(), 10).unwrap();
+        let mut stack = PolicyStack::new(Pid::this());
+        stack
+            .push(Attributes {
+                policy: Policy::RoundRobin,
+                nice: 0,
+                deadline_ns: 0,
+                period_ns: 0,
+                flags: SchedFlags::empty(),
+                priority: 20,
+                runtime_ns: 0,
+                sched_util_min: 0,
+                sched_util_max: 0,
+            })
+            .unwrap();
+        assert_eq!(stack.depth(), 1);
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::RoundRobin, 20));
+
+        stack.pop().unwrap();
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::Fifo, 10));
+        assert!(matches!(stack.pop(), Err(SchedError::EmptyPolicyStack)));
+    }
+
+    #[test]
+    fn test_scheduling_snapshot() {
+        use crate::{get_affinity, get_timer_slack, set_timer_slack};
+
+        set_timer_slack(50_000).unwrap();
+        let before = get_affinity(Pid::this()).unwrap();
+        {
+            let _snapshot = SchedulingSnapshot::capture(Pid::this()).unwrap();
+            set_fifo(Pid::this(), 30).unwrap();
+            set_affinity(
+                Pid::this(),
+                CpuSet::empty().set(before.iter().next().unwrap()),
+            )
+            .unwrap();
+            set_timer_slack(1).unwrap();
+        }
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!(a.policy, Policy::Normal);
+        assert_eq!(get_affinity(Pid::this()).unwrap(), before);
+        assert_eq!(get_timer_slack().unwrap(), 50_000);
+
+        let snapshot = SchedulingSnapshot::capture(Pid::this()).unwrap();
+        set_fifo(Pid::this(), 30).unwrap();
+        snapshot.restore().unwrap();
+        set_fifo(Pid::this(), 40).unwrap();
+        drop(snapshot);
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::Fifo, 40));
+    }
+
+    #[test]
+    fn test_policy_negotiator() {
+        let mut invalid = Attributes::for_fifo(10).unwrap();
+        invalid.priority = 200;
+        let rr = Attributes::for_rr(20).unwrap();
+        let negotiator = PolicyNegotiator::new(Pid::this());
+        let chosen = negotiator
+            .try_policies(&[rr.clone(), Attributes::for_normal(0).unwrap()])
+            .unwrap();
+        assert_eq!((chosen.policy, chosen.priority), (Policy::RoundRobin, 20));
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::RoundRobin, 20));
+
+        // Only EPERM falls back, other errors are returned right away.
+        assert!(matches!(
+            negotiator.try_policies(&[invalid, rr]),
+            Err(SchedError::Errno(Errno::EINVAL))
+        ));
+        assert!(matches!(
+            negotiator.try_policies(&[]),
+            Err(SchedError::NoCandidates)
+        ));
+    }
+
+    #[test]
+    fn test_policy_negotiator_fallback() {
+        // Drop the privileges of this thread only; the libc wrapper would change
+        // the credentials of every thread of the test binary.
+        let chosen = std::thread::spawn(|| {
+            let nobody = 65534;
+            assert_eq!(
+                unsafe { libc::syscall(libc::SYS_setresuid, nobody, nobody, nobody) },
+                0
+            );
+            let negotiator = PolicyNegotiator::new(Pid::this());
+            let fifo = Attributes::for_fifo(10).unwrap();
+            assert!(matches!(
+                negotiator.try_policies(std::slice::from_ref(&fifo)),
+                Err(SchedError::Errno(Errno::EPERM))
+            ));
+            negotiator.try_policies(&[fifo, Attributes::for_normal(0).unwrap()])
+        })
+        .join()
+        .unwrap()
+        .unwrap();
+        assert_eq!(chosen.policy, Policy::Normal);
+    }
+
+    #[test]
+    fn test_scoped_scheduling() {
+        set_fifo(Pid::this(), 10).unwrap();
+        let rr = Attributes::for_rr(20).unwrap();
+        scoped_scheduling(Pid::this(), rr.clone(), || {
+            let a = get_attr(Pid::this()).unwrap();
+            assert_eq!((a.policy, a.priority), (Policy::RoundRobin, 20));
+        })
+        .unwrap();
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::Fifo, 10));
+
+        let result = panic::catch_unwind(|| {
+            scoped_scheduling(Pid::this(), rr, || panic!("expected")).unwrap();
+        });
+        assert!(result.is_err());
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!((a.policy, a.priority), (Policy::Fifo, 10));
+    }
+
+    #[test]
+    fn test_scoped_affinity() {
+        let before = get_affinity(Pid::this()).unwrap();
+        let pinned = CpuSet::empty().set(before.iter().next().unwrap());
+        scoped_affinity(Pid::this(), pinned, || {
+            assert_eq!(get_affinity(Pid::this()).unwrap(), pinned);
+        })
+        .unwrap();
+        assert_eq!(get_affinity(Pid::this()).unwrap(), before);
+
+        let result = panic::catch_unwind(|| {
+            scoped_affinity(Pid::this(), pinned, || panic!("expected")).unwrap();
+        });
+        assert!(result.is_err());
+        assert_eq!(get_affinity(Pid::this()).unwrap(), before);
+    }
+}
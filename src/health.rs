@@ -0,0 +1,161 @@
+use std::{fs, io};
+
+use crate::{
+    get_affinity, get_attr, get_context_switches, get_isolated_cpus, prctl::get_timer_slack_of,
+    stats::status_field, Attributes, ContextSwitchStats, CpuSet, Pid, Policy, SchedError,
+};
+
+/// The real-time relevant configuration of a thread, as checked by
+/// `validate_rt_setup()`.
+#[derive(Debug)]
+pub struct RtSetupReport {
+    pub attributes: Attributes,
+    /// Amount of locked memory of the process in kB (`VmLck`).
+    pub locked_memory_kb: u64,
+    pub affinity: CpuSet,
+    pub isolated_cpus: CpuSet,
+    pub timer_slack_ns: u64,
+    pub context_switches: ContextSwitchStats,
+    /// Every check that did not pass, empty if the thread is set up for
+    /// real-time execution.
+    pub warnings: Vec<RtSetupWarning>,
+}
+
+/// A condition found by `validate_rt_setup()` that may cause latencies, but does
+/// not prevent the thread from running.
+#[derive(Debug)]
+pub enum RtSetupWarning {
+    /// The thread does not use `Fifo`, `RoundRobin` or `Deadline`.
+    NotRealtime(Policy),
+    /// The process has no locked memory, so page faults can occur, see
+    /// `mlockall()`.
+    MemoryNotLocked,
+    /// The affinity contains CPUs that are not isolated. Contains the affinity.
+    NotPinnedToIsolatedCpus(CpuSet),
+    /// The timer slack is larger than the minimum of 1 ns.
+    TimerSlack(u64),
+    /// The thread has been preempted this many times.
+    InvoluntaryContextSwitches(u64),
+    /// A value could not be read, so its check was skipped.
+    CheckFailed(&'static str, SchedError),
+}
+
+/// Checks whether the thread whose ID is specified in `pid` is set up for
+/// real-time execution: it uses a real-time policy, the memory of the process
+/// is locked, it is pinned to isolated CPUs, its timer slack is minimal and it
+/// has not been preempted yet.
+///
+/// Returns the checked values together with a warning for every check that did
+/// not pass. Fails only if a value could not be read, with a `CheckFailed`
+/// warning for each such value. Meant as a startup assertion, e.g.
+/// `assert!(validate_rt_setup(Pid::this()).unwrap().warnings.is_empty())` right
+/// before entering the real-time loop.
+pub fn validate_rt_setup(pid: Pid) -> Result<RtSetupReport, Vec<RtSetupWarning>> {
+    let mut warnings = Vec::new();
+    let attributes = check(&mut warnings, "attributes", get_attr(pid));
+    let locked_memory_kb = check(&mut warnings, "locked memory", locked_memory_kb(pid));
+    let affinity = check(&mut warnings, "affinity", get_affinity(pid));
+    let isolated_cpus = check(&mut warnings, "isolated CPUs", get_isolated_cpus());
+    let timer_slack_ns = check(&mut warnings, "timer slack", get_timer_slack_of(pid));
+    let context_switches = check(&mut warnings, "context switches", get_context_switches(pid));
+    let (
+        Some(attributes),
+        Some(locked_memory_kb),
+        Some(affinity),
+        Some(isolated_cpus),
+        Some(timer_slack_ns),
+        Some(context_switches),
+    ) = (
+        attributes,
+        locked_memory_kb,
+        affinity,
+        isolated_cpus,
+        timer_slack_ns,
+        context_switches,
+    )
+    else {
+        return Err(warnings);
+    };
+
+    if !matches!(
+        attributes.policy,
+        Policy::Fifo | Policy::RoundRobin | Policy::Deadline
+    ) {
+        warnings.push(RtSetupWarning::NotRealtime(attributes.policy));
+    }
+    if locked_memory_kb == 0 {
+        warnings.push(RtSetupWarning::MemoryNotLocked);
+    }
+    if affinity.iter().any(|cpu| !isolated_cpus.is_set(cpu)) {
+        warnings.push(RtSetupWarning::NotPinnedToIsolatedCpus(affinity));
+    }
+    if timer_slack_ns > 1 {
+        warnings.push(RtSetupWarning::TimerSlack(timer_slack_ns));
+    }
+    if context_switches.nonvoluntary > 0 {
+        warnings.push(RtSetupWarning::InvoluntaryContextSwitches(
+            context_switches.nonvoluntary,
+        ));
+    }
+    Ok(RtSetupReport {
+        attributes,
+        locked_memory_kb,
+        affinity,
+        isolated_cpus,
+        timer_slack_ns,
+        context_switches,
+        warnings,
+    })
+}
+
+/// Returns the value, or records a `CheckFailed` warning for `name`.
+fn check<T, E: Into<SchedError>>(
+    warnings: &mut Vec<RtSetupWarning>,
+    name: &'static str,
+    value: Result<T, E>,
+) -> Option<T> {
+    match value {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warnings.push(RtSetupWarning::CheckFailed(name, err.into()));
+            None
+        }
+    }
+}
+
+pub(crate) fn locked_memory_kb(pid: Pid) -> Result<u64, SchedError> {
+    let status = fs::read_to_string(pid.proc_path("status"))?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed VmLck");
+    let value = status_field(&status, "VmLck").ok_or_else(invalid)?;
+    Ok(value
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rt_setup() {
+        let report = validate_rt_setup(Pid::this()).unwrap();
+        assert_eq!(report.attributes.policy, Policy::Normal);
+        assert_eq!(report.affinity, get_affinity(Pid::this()).unwrap());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| matches!(w, RtSetupWarning::NotRealtime(Policy::Normal))));
+        assert!(report
+            .warnings
+            .iter()
+            .all(|w| !matches!(w, RtSetupWarning::CheckFailed(..))));
+
+        let warnings = validate_rt_setup(Pid::new(-1)).unwrap_err();
+        assert!(matches!(
+            warnings[0],
+            RtSetupWarning::CheckFailed("attributes", _)
+        ));
+    }
+}
@@ -1,7 +1,40 @@
 mod clock;
+mod cpuset;
+mod deadline;
+mod error;
+mod feasibility;
+mod guard;
+mod health;
 mod lowlevel;
+mod memory;
+mod mutex;
+mod prctl;
+mod rtlimit;
 mod sched;
+mod stats;
+mod system;
+mod task;
+mod thread;
+mod timer;
+mod timing;
 pub use clock::*;
-pub use lowlevel::clock::TimeSpec;
-pub use lowlevel::sched::CpuSet;
+pub use cpuset::*;
+pub use deadline::*;
+pub use error::*;
+pub use feasibility::*;
+pub use guard::*;
+pub use health::*;
+pub use lowlevel::clock::{SystemTimeRangeError, TimeSpec, TimeVal, Timex};
+pub use lowlevel::sched::{sched_setaffinity_raw, CpuSet, OutOfRangeError};
+pub use lowlevel::timerfd::ITimerSpec;
+pub use memory::*;
+pub use mutex::*;
+pub use prctl::*;
+pub use rtlimit::*;
 pub use sched::*;
+pub use stats::*;
+pub use system::*;
+pub use task::*;
+pub use thread::*;
+pub use timer::*;
+pub use timing::*;
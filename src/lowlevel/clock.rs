@@ -1,4 +1,9 @@
-use std::ffi::c_int;
+use std::{
+    error,
+    ffi::c_int,
+    fmt,
+    time::{Duration, SystemTime, SystemTimeError, UNIX_EPOCH},
+};
 
 use syscalls::{syscall, Errno, Sysno};
 
@@ -58,6 +63,52 @@ impl TimeSpec {
     pub const fn as_milliseconds(&self) -> i64 {
         self.tv_sec * 1_000 + self.tv_nsec / 1_000_000
     }
+
+    /// Converts a POSIX timestamp in seconds since the epoch, e.g. to sleep until
+    /// a wall-clock time on `ClockRealtime`.
+    pub const fn from_unix_timestamp(secs: i64) -> Self {
+        Self {
+            tv_sec: secs,
+            tv_nsec: 0,
+        }
+    }
+
+    /// Returns the seconds since the epoch. The fractional second is truncated.
+    pub const fn to_unix_timestamp(&self) -> i64 {
+        self.tv_sec
+    }
+
+    /// Converts a `ClockRealtime` time to the `Duration` since the Unix epoch, as
+    /// used by `std::time::SystemTime`. Returns `None` for times before the epoch.
+    pub fn as_duration_since_epoch(&self) -> Option<std::time::Duration> {
+        if self.tv_sec < 0 || self.tv_nsec < 0 {
+            return None;
+        }
+        Some(std::time::Duration::new(
+            self.tv_sec as u64,
+            self.tv_nsec as u32,
+        ))
+    }
+
+    /// Returns the time elapsed from `earlier` to `self`, or zero if `earlier` is
+    /// later than `self`, like `std::time::Instant::duration_since()`.
+    pub const fn elapsed_since(self, earlier: TimeSpec) -> TimeSpec {
+        match self.checked_elapsed_since(earlier) {
+            Some(elapsed) => elapsed,
+            None => TimeSpec::zeroed(),
+        }
+    }
+
+    /// Returns the time elapsed from `earlier` to `self`, or `None` if `earlier`
+    /// is later than `self`.
+    pub const fn checked_elapsed_since(self, earlier: TimeSpec) -> Option<TimeSpec> {
+        let elapsed = self.as_nanoseconds() - earlier.as_nanoseconds();
+        if elapsed >= 0 {
+            Some(TimeSpec::nanoseconds(elapsed))
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for TimeSpec {
@@ -66,6 +117,72 @@ impl Default for TimeSpec {
     }
 }
 
+#[cfg(feature = "libc")]
+impl From<libc::timespec> for TimeSpec {
+    fn from(ts: libc::timespec) -> Self {
+        Self {
+            tv_sec: ts.tv_sec as std::ffi::c_long,
+            tv_nsec: ts.tv_nsec as std::ffi::c_long,
+        }
+    }
+}
+
+#[cfg(feature = "libc")]
+impl From<TimeSpec> for libc::timespec {
+    fn from(ts: TimeSpec) -> Self {
+        libc::timespec {
+            tv_sec: ts.tv_sec as libc::time_t,
+            tv_nsec: ts.tv_nsec as _,
+        }
+    }
+}
+
+/// Converts a wall-clock time to the time since the epoch as used by
+/// `ClockRealtime`. Fails for times before the epoch.
+impl TryFrom<SystemTime> for TimeSpec {
+    type Error = SystemTimeError;
+
+    fn try_from(time: SystemTime) -> Result<Self, SystemTimeError> {
+        let since_epoch = time.duration_since(UNIX_EPOCH)?;
+        Ok(TimeSpec {
+            tv_sec: since_epoch.as_secs() as std::ffi::c_long,
+            tv_nsec: since_epoch.subsec_nanos() as std::ffi::c_long,
+        })
+    }
+}
+
+/// Error returned when a `TimeSpec` cannot be represented as a `SystemTime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTimeRangeError;
+
+impl fmt::Display for SystemTimeRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "time is out of the range of SystemTime")
+    }
+}
+
+impl error::Error for SystemTimeRangeError {}
+
+/// Converts a `ClockRealtime` time to a wall-clock time. Times before the epoch
+/// are supported.
+impl TryFrom<TimeSpec> for SystemTime {
+    type Error = SystemTimeRangeError;
+
+    fn try_from(ts: TimeSpec) -> Result<Self, SystemTimeRangeError> {
+        let nanos = ts.tv_sec as i128 * 1_000_000_000 + ts.tv_nsec as i128;
+        let offset = Duration::new(
+            (nanos.unsigned_abs() / 1_000_000_000) as u64,
+            (nanos.unsigned_abs() % 1_000_000_000) as u32,
+        );
+        let time = if nanos < 0 {
+            UNIX_EPOCH.checked_sub(offset)
+        } else {
+            UNIX_EPOCH.checked_add(offset)
+        };
+        time.ok_or(SystemTimeRangeError)
+    }
+}
+
 impl core::ops::Add for TimeSpec {
     type Output = TimeSpec;
 
@@ -82,11 +199,80 @@ impl core::ops::Sub for TimeSpec {
     }
 }
 
+impl core::ops::Neg for TimeSpec {
+    type Output = TimeSpec;
+
+    /// Negates the time. `i64::MIN` nanoseconds saturate to `i64::MAX`
+    /// nanoseconds, as their negation is not representable.
+    fn neg(self) -> TimeSpec {
+        TimeSpec::nanoseconds(self.as_nanoseconds().saturating_neg())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct TimeVal {
+    pub tv_sec: std::ffi::c_long,
+    pub tv_usec: std::ffi::c_long,
+}
+
+/// Kernel clock state returned by `adjtimex()`, see adjtimex(2).
+#[repr(C)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Timex {
+    /// Mode selector
+    pub modes: std::ffi::c_uint,
+    /// Time offset; nanoseconds, if STA_NANO status flag is set, otherwise microseconds
+    pub offset: std::ffi::c_long,
+    /// Frequency offset
+    pub freq: std::ffi::c_long,
+    /// Maximum error (microseconds)
+    pub maxerror: std::ffi::c_long,
+    /// Estimated error (microseconds)
+    pub esterror: std::ffi::c_long,
+    /// Clock command/status
+    pub status: c_int,
+    /// PLL (phase-locked loop) time constant
+    pub constant: std::ffi::c_long,
+    /// Clock precision (microseconds, read-only)
+    pub precision: std::ffi::c_long,
+    /// Clock frequency tolerance (read-only)
+    pub tolerance: std::ffi::c_long,
+    /// Current time (read-only, except for ADJ_SETOFFSET)
+    pub time: TimeVal,
+    /// Microseconds between clock ticks
+    pub tick: std::ffi::c_long,
+    /// PPS (pulse per second) frequency (read-only)
+    pub ppsfreq: std::ffi::c_long,
+    /// PPS jitter (read-only)
+    pub jitter: std::ffi::c_long,
+    /// PPS interval duration (seconds, read-only)
+    pub shift: c_int,
+    /// PPS stability (read-only)
+    pub stabil: std::ffi::c_long,
+    /// PPS count of jitter limit exceeded events (read-only)
+    pub jitcnt: std::ffi::c_long,
+    /// PPS count of calibration intervals (read-only)
+    pub calcnt: std::ffi::c_long,
+    /// PPS count of calibration errors (read-only)
+    pub errcnt: std::ffi::c_long,
+    /// PPS count of stability limit exceeded events (read-only)
+    pub stbcnt: std::ffi::c_long,
+    /// TAI offset, as set by previous ADJ_TAI operation (seconds, read-only)
+    pub tai: c_int,
+    padding: [c_int; 11],
+}
+
 #[allow(clippy::missing_safety_doc)]
 pub unsafe fn clock_gettime(clockid: clockid_t, tp: *mut TimeSpec) -> Result<usize, Errno> {
     syscall!(Sysno::clock_gettime, clockid, tp)
 }
 
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn clock_getres(clockid: clockid_t, res: *mut TimeSpec) -> Result<usize, Errno> {
+    syscall!(Sysno::clock_getres, clockid, res)
+}
+
 #[allow(clippy::missing_safety_doc)]
 pub unsafe fn clock_settime(clockid: clockid_t, tp: *const TimeSpec) -> Result<usize, Errno> {
     syscall!(Sysno::clock_settime, clockid, tp)
@@ -104,10 +290,122 @@ pub unsafe fn clock_nanosleep(
     syscall!(Sysno::clock_nanosleep, clockid, flags, tp, remain)
 }
 
+/// Reads and optionally sets the parameters of the kernel clock adjustment
+/// algorithm. Returns the clock state, e.g. `TIME_OK`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn adjtimex(tx: *mut Timex) -> Result<i32, Errno> {
+    syscall!(Sysno::adjtimex, tx).map(|state| state as i32)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_adjtimex() {
+        let mut tx = Timex::default();
+        let state = unsafe { adjtimex(&mut tx) }.unwrap();
+        assert!((0..=5).contains(&state));
+        assert!(tx.tick > 0);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(core::mem::size_of::<Timex>(), 208);
+    }
+
+    #[cfg(feature = "libc")]
+    #[test]
+    fn test_libc_timespec() {
+        let ts = TimeSpec::nanoseconds(1_500_000_000);
+        let libc_ts = libc::timespec::from(ts);
+        assert_eq!((libc_ts.tv_sec, libc_ts.tv_nsec), (1, 500_000_000));
+        assert_eq!(TimeSpec::from(libc_ts), ts);
+    }
+
+    #[test]
+    fn test_unix_timestamp() {
+        let ts = TimeSpec::from_unix_timestamp(1_700_000_000);
+        assert_eq!(
+            ts,
+            TimeSpec {
+                tv_sec: 1_700_000_000,
+                tv_nsec: 0
+            }
+        );
+        let ts = ts + TimeSpec::nanoseconds(999_999_999);
+        assert_eq!(ts.to_unix_timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_duration_since_epoch() {
+        let ts = TimeSpec::nanoseconds(1_500_000_000);
+        assert_eq!(
+            ts.as_duration_since_epoch(),
+            Some(std::time::Duration::from_millis(1_500))
+        );
+        assert_eq!(TimeSpec::nanoseconds(-1).as_duration_since_epoch(), None);
+        assert_eq!(
+            TimeSpec::from_unix_timestamp(-10).as_duration_since_epoch(),
+            None
+        );
+
+        let mut now = TimeSpec::zeroed();
+        unsafe { clock_gettime(CLOCK_REALTIME, &mut now) }.unwrap();
+        let system = std::time::SystemTime::UNIX_EPOCH + now.as_duration_since_epoch().unwrap();
+        let diff = std::time::SystemTime::now().duration_since(system).unwrap();
+        assert!(diff.as_secs() < 1);
+    }
+
+    #[test]
+    fn test_system_time() {
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 250);
+        let ts = TimeSpec::try_from(time).unwrap();
+        assert_eq!(
+            ts,
+            TimeSpec {
+                tv_sec: 1_700_000_000,
+                tv_nsec: 250
+            }
+        );
+        assert_eq!(SystemTime::try_from(ts), Ok(time));
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1_500);
+        assert!(TimeSpec::try_from(before_epoch).is_err());
+        assert_eq!(
+            SystemTime::try_from(TimeSpec::nanoseconds(-1_500_000_000)),
+            Ok(before_epoch)
+        );
+    }
+
+    #[test]
+    fn test_elapsed_since() {
+        let earlier = TimeSpec::nanoseconds(1_500_000_000);
+        let later = TimeSpec::nanoseconds(2_250_000_000);
+        assert_eq!(
+            later.elapsed_since(earlier),
+            TimeSpec::nanoseconds(750_000_000)
+        );
+        assert_eq!(
+            later.checked_elapsed_since(earlier),
+            Some(TimeSpec::nanoseconds(750_000_000))
+        );
+        assert_eq!(earlier.elapsed_since(later), TimeSpec::zeroed());
+        assert_eq!(earlier.checked_elapsed_since(later), None);
+        assert_eq!(later.elapsed_since(later), TimeSpec::zeroed());
+    }
+
+    #[test]
+    fn test_neg() {
+        assert_eq!(
+            -TimeSpec::nanoseconds(1_500_000_000),
+            TimeSpec::nanoseconds(-1_500_000_000)
+        );
+        assert_eq!(-TimeSpec::nanoseconds(-7), TimeSpec::nanoseconds(7));
+        assert_eq!(-TimeSpec::zeroed(), TimeSpec::zeroed());
+        assert_eq!(
+            -TimeSpec::nanoseconds(i64::MIN),
+            TimeSpec::nanoseconds(i64::MAX)
+        );
+    }
+
     #[test]
     fn test_nanos() {
         assert_eq!(
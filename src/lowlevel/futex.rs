@@ -0,0 +1,142 @@
+use std::{ffi::c_int, ptr};
+
+use syscalls::{syscall, Errno, Sysno};
+
+use super::clock::TimeSpec;
+
+pub const FUTEX_LOCK_PI: c_int = 6;
+pub const FUTEX_UNLOCK_PI: c_int = 7;
+#[allow(dead_code)]
+pub const FUTEX_TRYLOCK_PI: c_int = 8;
+/// Like `FUTEX_LOCK_PI`, but the timeout is measured against `CLOCK_MONOTONIC`
+/// unless `FUTEX_CLOCK_REALTIME` is set (since Linux 5.14).
+pub const FUTEX_LOCK_PI2: c_int = 13;
+/// Marks the futex as only used within the process, which saves the kernel
+/// from looking up the shared mapping.
+pub const FUTEX_PRIVATE_FLAG: c_int = 128;
+#[allow(dead_code)]
+pub const FUTEX_CLOCK_REALTIME: c_int = 256;
+
+/// Set in the futex word of a PI futex if other threads are blocked on it.
+#[allow(dead_code)]
+pub const FUTEX_WAITERS: u32 = 0x8000_0000;
+/// Set in the futex word of a PI futex if its owner died while holding it.
+#[allow(dead_code)]
+pub const FUTEX_OWNER_DIED: u32 = 0x4000_0000;
+/// Mask of the owner TID in the futex word of a PI futex.
+#[allow(dead_code)]
+pub const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// Acquires the priority-inheritance futex at `addr`, blocking until it is free.
+/// The futex word holds the TID of its owner, or 0 if it is free. While the
+/// calling thread blocks, the owner inherits its priority.
+///
+/// `timeout` is an absolute time and may be null to wait forever. If `clockrt`
+/// is set, it is measured against `CLOCK_REALTIME` (`FUTEX_LOCK_PI`), otherwise
+/// against `CLOCK_MONOTONIC` (`FUTEX_LOCK_PI2`, since Linux 5.14). Returns
+/// `ETIMEDOUT` if the timeout expired and `EDEADLK` if the futex is already held
+/// by the calling thread. Kernels before 5.14 return `ENOSYS` for
+/// `FUTEX_LOCK_PI2`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn futex_lock_pi(
+    addr: *mut i32,
+    timeout: *const TimeSpec,
+    clockrt: bool,
+) -> Result<(), Errno> {
+    lock_pi(addr, timeout, clockrt, 0)
+}
+
+/// Like `futex_lock_pi()`, but with `FUTEX_PRIVATE_FLAG`: the futex must not be
+/// shared with other processes and must be released with
+/// `futex_unlock_pi_private()`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn futex_lock_pi_private(
+    addr: *mut i32,
+    timeout: *const TimeSpec,
+    clockrt: bool,
+) -> Result<(), Errno> {
+    lock_pi(addr, timeout, clockrt, FUTEX_PRIVATE_FLAG)
+}
+
+unsafe fn lock_pi(
+    addr: *mut i32,
+    timeout: *const TimeSpec,
+    clockrt: bool,
+    flags: c_int,
+) -> Result<(), Errno> {
+    let op = if clockrt {
+        FUTEX_LOCK_PI
+    } else {
+        FUTEX_LOCK_PI2
+    };
+    syscall!(
+        Sysno::futex,
+        addr,
+        op | flags,
+        0,
+        timeout,
+        ptr::null::<u32>(),
+        0
+    )
+    .and(Ok(()))
+}
+
+/// Releases the priority-inheritance futex at `addr`, which must be held by the
+/// calling thread, and wakes the highest priority waiter.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn futex_unlock_pi(addr: *mut i32) -> Result<(), Errno> {
+    syscall!(Sysno::futex, addr, FUTEX_UNLOCK_PI, 0, 0, 0, 0).and(Ok(()))
+}
+
+/// Like `futex_unlock_pi()`, for a futex acquired with `futex_lock_pi_private()`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn futex_unlock_pi_private(addr: *mut i32) -> Result<(), Errno> {
+    syscall!(
+        Sysno::futex,
+        addr,
+        FUTEX_UNLOCK_PI | FUTEX_PRIVATE_FLAG,
+        0,
+        0,
+        0,
+        0
+    )
+    .and(Ok(()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lowlevel::clock::{clock_gettime, CLOCK_MONOTONIC};
+
+    #[test]
+    fn test_futex_pi() {
+        let tid = unsafe { libc::gettid() };
+        let mut word = 0i32;
+        unsafe { futex_lock_pi(&mut word, ptr::null(), true) }.unwrap();
+        assert_eq!(word, tid);
+        assert_eq!(
+            unsafe { futex_lock_pi(&mut word, ptr::null(), true) },
+            Err(Errno::EDEADLK)
+        );
+        unsafe { futex_unlock_pi(&mut word) }.unwrap();
+        assert_eq!(word, 0);
+        assert_eq!(unsafe { futex_unlock_pi(&mut word) }, Err(Errno::EPERM));
+
+        unsafe { futex_lock_pi_private(&mut word, ptr::null(), true) }.unwrap();
+        assert_eq!(word, tid);
+        unsafe { futex_unlock_pi_private(&mut word) }.unwrap();
+        assert_eq!(word, 0);
+
+        let word = std::sync::Arc::new(std::sync::atomic::AtomicI32::new(tid));
+        let other = word.clone();
+        std::thread::spawn(move || {
+            let mut timeout = TimeSpec::zeroed();
+            unsafe { clock_gettime(CLOCK_MONOTONIC, &mut timeout) }.unwrap();
+            let timeout = timeout + TimeSpec::nanoseconds(1_000_000);
+            let ret = unsafe { futex_lock_pi_private(other.as_ptr(), &timeout, false) };
+            assert_eq!(ret, Err(Errno::ETIMEDOUT));
+        })
+        .join()
+        .unwrap();
+    }
+}
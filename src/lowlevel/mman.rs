@@ -0,0 +1,23 @@
+use std::ffi::c_int;
+
+use syscalls::{syscall, Errno, Sysno};
+
+/// Lock all pages that are currently mapped into the address space.
+pub const MCL_CURRENT: c_int = 1;
+/// Lock all pages that will become mapped in the future.
+pub const MCL_FUTURE: c_int = 2;
+/// Lock pages only when they are faulted in. Used together with the other flags.
+#[allow(dead_code)]
+pub const MCL_ONFAULT: c_int = 4;
+
+/// Locks the address space of the calling process in RAM, see mlockall(2).
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn mlockall(flags: c_int) -> Result<usize, Errno> {
+    syscall!(Sysno::mlockall, flags)
+}
+
+/// Unlocks all pages of the calling process.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn munlockall() -> Result<usize, Errno> {
+    syscall!(Sysno::munlockall)
+}
@@ -1,2 +1,6 @@
 pub mod clock;
+pub mod futex;
+pub mod mman;
+pub mod prctl;
 pub mod sched;
+pub mod timerfd;
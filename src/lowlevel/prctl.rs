@@ -0,0 +1,33 @@
+use std::ffi::c_int;
+
+use syscalls::{syscall, Errno, Sysno};
+
+pub const PR_GET_DUMPABLE: c_int = 3;
+pub const PR_SET_DUMPABLE: c_int = 4;
+pub const PR_SET_TIMERSLACK: c_int = 29;
+pub const PR_GET_TIMERSLACK: c_int = 30;
+
+/// Performs the operation `option` on the calling thread or process. The
+/// meaning of the remaining arguments depends on `option`, unused arguments
+/// should be zero.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn prctl(
+    option: c_int,
+    arg2: usize,
+    arg3: usize,
+    arg4: usize,
+    arg5: usize,
+) -> Result<usize, Errno> {
+    syscall!(Sysno::prctl, option, arg2, arg3, arg4, arg5)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prctl() {
+        let slack = unsafe { prctl(PR_GET_TIMERSLACK, 0, 0, 0, 0) }.unwrap();
+        assert!(slack > 0);
+    }
+}
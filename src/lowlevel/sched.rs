@@ -1,4 +1,9 @@
-use std::ffi::c_int;
+use std::{
+    error,
+    ffi::c_int,
+    fmt,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign},
+};
 
 use syscalls::{syscall, Errno, Sysno};
 
@@ -13,6 +18,10 @@ pub const SCHED_IDLE: u32 = 5;
 pub const SCHED_DEADLINE: u32 = 6;
 pub const SCHED_EXT: u32 = 7;
 
+/// Can be OR-ed into the policy of `sched_setscheduler()`, and is reported by
+/// `sched_getscheduler()`, if children should not inherit privileged policies.
+pub const SCHED_RESET_ON_FORK: u32 = 0x40000000;
+
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct SchedAttr {
@@ -58,6 +67,43 @@ pub unsafe fn sched_get_attr(
     syscall!(Sysno::sched_getattr, pid, attr, size, flags)
 }
 
+#[repr(C)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SchedParam {
+    /// Static priority (SCHED_FIFO, SCHED_RR)
+    pub sched_priority: c_int,
+}
+
+/// Sets both the scheduling policy and parameters for the thread whose ID is
+/// specified in `pid`. If `pid` equals zero, the calling thread is used.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn sched_setscheduler(
+    pid: pid_t,
+    policy: c_int,
+    param: *const SchedParam,
+) -> Result<usize, Errno> {
+    syscall!(Sysno::sched_setscheduler, pid, policy, param)
+}
+
+/// Returns the current scheduling policy of the thread identified by `pid`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn sched_getscheduler(pid: pid_t) -> Result<usize, Errno> {
+    syscall!(Sysno::sched_getscheduler, pid)
+}
+
+/// Retrieves the scheduling parameters of the thread identified by `pid`.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn sched_getparam(pid: pid_t, param: *mut SchedParam) -> Result<usize, Errno> {
+    syscall!(Sysno::sched_getparam, pid, param)
+}
+
+/// Sets the scheduling parameters of the thread identified by `pid` without
+/// changing its policy.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn sched_setparam(pid: pid_t, param: *const SchedParam) -> Result<usize, Errno> {
+    syscall!(Sysno::sched_setparam, pid, param)
+}
+
 #[cfg(target_pointer_width = "32")]
 const CPU_SET_SIZE: usize = 32;
 #[cfg(target_pointer_width = "32")]
@@ -66,9 +112,10 @@ type Map = u32;
 const CPU_SET_SIZE: usize = 16;
 #[cfg(not(target_pointer_width = "32"))]
 type Map = u64;
+const MAP_BITS: usize = Map::BITS as usize;
 
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct CpuSet {
     bits: [Map; CPU_SET_SIZE],
 }
@@ -91,31 +138,193 @@ impl CpuSet {
         self
     }
 
+    /// Adds CPU `core` to the set.
+    ///
+    /// Panics if `core` is not below `CpuSet::n_cpus()`, see `try_set()`.
     pub const fn set(self, core: usize) -> Self {
+        assert!(
+            core < Self::n_cpus(),
+            "CpuSet index out of bounds, CpuSet supports 0..1024"
+        );
         let mut cs = self;
-        let idx = core / size_of::<Map>();
-        let bit = core % size_of::<Map>();
+        let idx = core / MAP_BITS;
+        let bit = core % MAP_BITS;
         cs.bits[idx] |= 1 << bit;
         cs
     }
 
+    /// Removes CPU `core` from the set.
+    ///
+    /// Panics if `core` is not below `CpuSet::n_cpus()`, see `try_clear()`.
     pub const fn clear(self, core: usize) -> Self {
+        assert!(
+            core < Self::n_cpus(),
+            "CpuSet index out of bounds, CpuSet supports 0..1024"
+        );
         let mut cs = self;
-        let idx = core / size_of::<Map>();
-        let bit = core % size_of::<Map>();
-        cs.bits[idx] &= 1 << bit;
+        let idx = core / MAP_BITS;
+        let bit = core % MAP_BITS;
+        cs.bits[idx] &= !(1 << bit);
         cs
     }
 
-    pub const fn is_set(&mut self, core: usize) -> bool {
-        let idx = core / size_of::<Map>();
-        let bit = core % size_of::<Map>();
+    /// Like `set()`, but fails instead of panicking for an invalid `core`.
+    pub fn try_set(self, core: usize) -> Result<Self, OutOfRangeError> {
+        if core < Self::n_cpus() {
+            Ok(self.set(core))
+        } else {
+            Err(OutOfRangeError(core))
+        }
+    }
+
+    /// Like `clear()`, but fails instead of panicking for an invalid `core`.
+    pub fn try_clear(self, core: usize) -> Result<Self, OutOfRangeError> {
+        if core < Self::n_cpus() {
+            Ok(self.clear(core))
+        } else {
+            Err(OutOfRangeError(core))
+        }
+    }
+
+    /// Returns `true` if CPU `core` is in the set, which is never the case for a
+    /// `core` that is not below `CpuSet::n_cpus()`.
+    pub const fn is_set(&self, core: usize) -> bool {
+        if core >= Self::n_cpus() {
+            return false;
+        }
+        let idx = core / MAP_BITS;
+        let bit = core % MAP_BITS;
         self.bits[idx] & (1 << bit) > 0
     }
 
     pub const fn size_of() -> usize {
         size_of::<Self>()
     }
+
+    /// Returns the number of CPUs a set can represent. Valid CPU indices are
+    /// `0..CpuSet::n_cpus()`.
+    pub const fn n_cpus() -> usize {
+        CPU_SET_SIZE * MAP_BITS
+    }
+
+    /// Returns the number of CPUs in the set.
+    pub const fn count(&self) -> usize {
+        let mut count = 0;
+        let mut idx = 0;
+        while idx < CPU_SET_SIZE {
+            count += self.bits[idx].count_ones() as usize;
+            idx += 1;
+        }
+        count
+    }
+
+    /// Returns the number of CPUs that are in both `self` and `other`.
+    pub const fn count_common_with(&self, other: &CpuSet) -> usize {
+        let mut count = 0;
+        let mut idx = 0;
+        while idx < CPU_SET_SIZE {
+            count += (self.bits[idx] & other.bits[idx]).count_ones() as usize;
+            idx += 1;
+        }
+        count
+    }
+
+    /// Returns the CPUs that are in exactly one of `self` and `other`.
+    pub const fn symmetric_difference(&self, other: &CpuSet) -> CpuSet {
+        let mut cs = CpuSet::empty();
+        let mut idx = 0;
+        while idx < CPU_SET_SIZE {
+            cs.bits[idx] = self.bits[idx] ^ other.bits[idx];
+            idx += 1;
+        }
+        cs
+    }
+
+    /// Returns an iterator over the indices of all CPUs contained in the set,
+    /// in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::n_cpus()).filter(move |&core| self.is_set(core))
+    }
+}
+
+/// Error returned by `CpuSet::try_set()` and `CpuSet::try_clear()` for a CPU
+/// index that a `CpuSet` cannot represent. Contains the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRangeError(pub usize);
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} out of bounds, CpuSet supports 0..{}",
+            self.0,
+            CpuSet::n_cpus()
+        )
+    }
+}
+
+impl error::Error for OutOfRangeError {}
+
+impl Default for CpuSet {
+    /// Returns `CpuSet::empty()`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// Keeps the CPUs that are in both sets.
+impl BitAndAssign for CpuSet {
+    fn bitand_assign(&mut self, rhs: CpuSet) {
+        for (bits, rhs) in self.bits.iter_mut().zip(rhs.bits) {
+            *bits &= rhs;
+        }
+    }
+}
+
+/// Adds the CPUs of `rhs`.
+impl BitOrAssign for CpuSet {
+    fn bitor_assign(&mut self, rhs: CpuSet) {
+        for (bits, rhs) in self.bits.iter_mut().zip(rhs.bits) {
+            *bits |= rhs;
+        }
+    }
+}
+
+/// Keeps the CPUs that are in exactly one of the sets, see
+/// `CpuSet::symmetric_difference()`.
+impl BitXorAssign for CpuSet {
+    fn bitxor_assign(&mut self, rhs: CpuSet) {
+        for (bits, rhs) in self.bits.iter_mut().zip(rhs.bits) {
+            *bits ^= rhs;
+        }
+    }
+}
+
+impl BitAnd for CpuSet {
+    type Output = CpuSet;
+
+    fn bitand(mut self, rhs: CpuSet) -> CpuSet {
+        self &= rhs;
+        self
+    }
+}
+
+impl BitOr for CpuSet {
+    type Output = CpuSet;
+
+    fn bitor(mut self, rhs: CpuSet) -> CpuSet {
+        self |= rhs;
+        self
+    }
+}
+
+impl BitXor for CpuSet {
+    type Output = CpuSet;
+
+    fn bitxor(mut self, rhs: CpuSet) -> CpuSet {
+        self ^= rhs;
+        self
+    }
 }
 
 /// Sets the CPU affinity mask of the thread whose
@@ -136,6 +345,22 @@ pub unsafe fn sched_set_affinity(
     syscall!(Sysno::sched_setaffinity, pid, cpusetsize, mask)
 }
 
+/// Like `sched_set_affinity()`, but takes the mask as `setsize` raw bytes at
+/// `bits` instead of a `CpuSet`, in the layout of `cpu_set_t`: CPU `n` is bit
+/// `n % 8` of byte `n / 8` on little-endian machines. Allows passing masks that
+/// are kept in foreign memory without copying them.
+///
+/// # Safety
+///
+/// `bits` must point to `setsize` readable bytes.
+pub unsafe fn sched_setaffinity_raw(
+    pid: pid_t,
+    setsize: usize,
+    bits: *const u8,
+) -> Result<usize, Errno> {
+    syscall!(Sysno::sched_setaffinity, pid, setsize, bits)
+}
+
 /// writes the affinity mask of the thread whose
 /// ID is pid into the cpu_set_t structure pointed to by mask.  The
 /// cpusetsize argument specifies the size (in bytes) of mask.  If pid
@@ -151,6 +376,19 @@ pub unsafe fn sched_get_affinity(
     syscall!(Sysno::sched_getaffinity, pid, cpusetsize, mask)
 }
 
+/// Returns the process ID of the calling process, which is the thread ID of its
+/// main thread. This call always succeeds.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn getpid() -> Result<usize, Errno> {
+    syscall!(Sysno::getpid)
+}
+
+/// Returns the thread ID of the calling thread. This call always succeeds.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn gettid() -> Result<usize, Errno> {
+    syscall!(Sysno::gettid)
+}
+
 #[allow(clippy::missing_safety_doc)]
 pub unsafe fn sched_yield() -> Result<usize, Errno> {
     syscall!(Sysno::sched_yield)
@@ -194,6 +432,19 @@ mod test {
         assert_eq!(attr2.sched_policy, { SCHED_IDLE });
     }
 
+    #[test]
+    fn test_setscheduler() {
+        let param = SchedParam { sched_priority: 10 };
+        let ret = unsafe { sched_setscheduler(0, SCHED_FIFO as c_int, &param) };
+        assert_eq!(ret, Ok(0));
+        let ret = unsafe { sched_getscheduler(0) };
+        assert_eq!(ret, Ok(SCHED_FIFO as usize));
+        let mut param = SchedParam::default();
+        let ret = unsafe { sched_getparam(0, &mut param) };
+        assert_eq!(ret, Ok(0));
+        assert_eq!(param.sched_priority, 10);
+    }
+
     #[test]
     fn test_cpuset() {
         let test = CpuSet::full();
@@ -217,6 +468,118 @@ mod test {
                 bits: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
             }
         );
+
+        let test = CpuSet::empty().set(65);
+        #[cfg(not(target_pointer_width = "32"))]
+        assert_eq!(
+            test,
+            CpuSet {
+                bits: [0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            }
+        );
+    }
+
+    #[test]
+    fn test_cpuset_iter() {
+        let test = CpuSet::empty().set(0).set(3).set(64).set(127);
+        assert_eq!(test.iter().collect::<Vec<_>>(), vec![0, 3, 64, 127]);
+        assert_eq!(CpuSet::empty().iter().count(), 0);
+        assert_eq!(CpuSet::default(), CpuSet::empty());
+        assert_eq!(test.count(), 4);
+        assert_eq!(CpuSet::full().count(), CpuSet::size_of() * 8);
+    }
+
+    #[test]
+    fn test_cpuset_symmetric_difference() {
+        let a = CpuSet::empty().set(0).set(1).set(64);
+        let b = CpuSet::empty().set(1).set(2).set(64).set(100);
+        let diff = a.symmetric_difference(&b);
+        assert_eq!(diff.iter().collect::<Vec<_>>(), vec![0, 2, 100]);
+        assert_eq!(b.symmetric_difference(&a), diff);
+        assert_eq!(a.symmetric_difference(&a), CpuSet::empty());
+    }
+
+    #[test]
+    fn test_cpuset_bit_ops() {
+        let a = CpuSet::empty().set(0).set(1).set(64);
+        let b = CpuSet::empty().set(1).set(2).set(64).set(100);
+        let cpus = |set: CpuSet| set.iter().collect::<Vec<_>>();
+        assert_eq!(cpus(a & b), vec![1, 64]);
+        assert_eq!(cpus(a | b), vec![0, 1, 2, 64, 100]);
+        assert_eq!(a ^ b, a.symmetric_difference(&b));
+
+        let mut set = a;
+        set &= b;
+        assert_eq!(set, a & b);
+        set |= CpuSet::empty().set(3);
+        assert_eq!(cpus(set), vec![1, 3, 64]);
+        set ^= a;
+        assert_eq!(cpus(set), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_cpuset_copy() {
+        let pinned = CpuSet::empty().set(2);
+        let saved = pinned;
+        let mut set = saved;
+        unsafe { (*set.as_mut_raw()).bits[0] = 1 };
+        assert_eq!(saved, pinned);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_cpuset_clear() {
+        assert!(CpuSet::full().clear(1).is_set(0));
+        let set = CpuSet::empty().set(0).set(1).set(64).clear(1);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 64]);
+        assert_eq!(CpuSet::full().clear(5).count(), CpuSet::n_cpus() - 1);
+        assert_eq!(CpuSet::empty().clear(5), CpuSet::empty());
+    }
+
+    #[test]
+    fn test_cpuset_bounds() {
+        let last = CpuSet::n_cpus() - 1;
+        assert!(CpuSet::empty().try_set(last).unwrap().is_set(last));
+        assert_eq!(
+            CpuSet::empty().try_set(CpuSet::n_cpus()),
+            Err(OutOfRangeError(1024))
+        );
+        assert_eq!(
+            CpuSet::full().try_clear(1025).unwrap_err().to_string(),
+            "index 1025 out of bounds, CpuSet supports 0..1024"
+        );
+        assert!(!CpuSet::full().is_set(CpuSet::n_cpus()));
+        assert!(!CpuSet::full().is_set(usize::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "CpuSet index out of bounds, CpuSet supports 0..1024")]
+    fn test_cpuset_set_out_of_bounds() {
+        let _ = CpuSet::empty().set(CpuSet::n_cpus());
+    }
+
+    #[test]
+    fn test_cpuset_const() {
+        const MASK: CpuSet = CpuSet::empty().set(3).set(4).clear(4);
+        assert!(MASK.is_set(3));
+        assert_eq!(MASK.count(), 1);
+    }
+
+    #[test]
+    fn test_cpuset_n_cpus() {
+        assert_eq!(CpuSet::n_cpus(), 1024);
+        assert_eq!(CpuSet::n_cpus(), CpuSet::size_of() * 8);
+        assert_eq!(CpuSet::full().count(), CpuSet::n_cpus());
+    }
+
+    #[test]
+    fn test_cpuset_count_common_with() {
+        let a = CpuSet::empty().set(0).set(1).set(64);
+        let b = CpuSet::empty().set(1).set(2).set(64).set(100);
+        assert_eq!(a.count_common_with(&b), 2);
+        assert_eq!(b.count_common_with(&a), 2);
+        assert_eq!(a.count_common_with(&CpuSet::full()), a.count());
+        assert_eq!(a.count_common_with(&CpuSet::empty()), 0);
     }
 
     #[test]
@@ -236,4 +599,23 @@ mod test {
 
         assert_eq!(ret, Ok(0))
     }
+
+    #[test]
+    fn test_setaffinity_raw() {
+        std::thread::spawn(|| {
+            let cpu = crate::sched::get_affinity(crate::sched::Pid::this())
+                .unwrap()
+                .iter()
+                .next()
+                .unwrap();
+            let mut bits = vec![0u8; cpu / 8 + 1];
+            bits[cpu / 8] = 1 << (cpu % 8);
+            unsafe { sched_setaffinity_raw(0, bits.len(), bits.as_ptr()) }.unwrap();
+            let mut cs = CpuSet::empty();
+            unsafe { sched_get_affinity(0, CpuSet::size_of(), cs.as_mut_raw()) }.unwrap();
+            assert_eq!(cs, CpuSet::empty().set(cpu));
+        })
+        .join()
+        .unwrap();
+    }
 }
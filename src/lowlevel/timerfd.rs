@@ -0,0 +1,99 @@
+use std::{ffi::c_int, os::fd::RawFd};
+
+use syscalls::{syscall, Errno, Sysno};
+
+use super::clock::{clockid_t, TimeSpec};
+
+#[allow(dead_code)]
+pub const TFD_TIMER_ABSTIME: c_int = 0x01;
+#[allow(dead_code)]
+pub const TFD_TIMER_CANCEL_ON_SET: c_int = 0x02;
+#[allow(dead_code)]
+pub const TFD_NONBLOCK: c_int = 0o4000;
+pub const TFD_CLOEXEC: c_int = 0o2000000;
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ITimerSpec {
+    /// Interval for periodic timers, zero for one-shot timers
+    pub interval: TimeSpec,
+    /// Initial expiration, zero disarms the timer
+    pub value: TimeSpec,
+}
+
+/// Creates a new timer object, and returns a file descriptor that refers to
+/// that timer. `clockid` specifies the clock that is used to mark the progress
+/// of the timer.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn timerfd_create(clockid: clockid_t, flags: i32) -> Result<RawFd, Errno> {
+    syscall!(Sysno::timerfd_create, clockid, flags).map(|fd| fd as RawFd)
+}
+
+/// Arms (starts) or disarms (stops) the timer referred to by `fd`.
+/// # Parameter
+///  * `old` nullable, receives the previous setting of the timer
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn timerfd_settime(
+    fd: RawFd,
+    flags: i32,
+    new: &ITimerSpec,
+    old: *mut ITimerSpec,
+) -> Result<(), Errno> {
+    syscall!(
+        Sysno::timerfd_settime,
+        fd,
+        flags,
+        new as *const ITimerSpec,
+        old
+    )
+    .and(Ok(()))
+}
+
+/// Returns the current setting of the timer referred to by `fd` in `curr`.
+#[allow(clippy::missing_safety_doc, dead_code)]
+pub unsafe fn timerfd_gettime(fd: RawFd, curr: &mut ITimerSpec) -> Result<(), Errno> {
+    syscall!(Sysno::timerfd_gettime, fd, curr as *mut ITimerSpec).and(Ok(()))
+}
+
+/// Reads the number of expirations that have occurred since the timer was last
+/// read. Blocks until the next expiration if there were none.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn timerfd_read(fd: RawFd) -> Result<u64, Errno> {
+    let mut expirations: u64 = 0;
+    syscall!(
+        Sysno::read,
+        fd,
+        &mut expirations as *mut u64,
+        core::mem::size_of::<u64>()
+    )
+    .and(Ok(expirations))
+}
+
+#[allow(clippy::missing_safety_doc)]
+pub unsafe fn close(fd: RawFd) -> Result<(), Errno> {
+    syscall!(Sysno::close, fd).and(Ok(()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lowlevel::clock::CLOCK_MONOTONIC;
+
+    #[test]
+    fn test_timerfd() {
+        let fd = unsafe { timerfd_create(CLOCK_MONOTONIC, TFD_CLOEXEC) }.unwrap();
+        let new = ITimerSpec {
+            interval: TimeSpec::nanoseconds(1_000_000),
+            value: TimeSpec::nanoseconds(10_000_000_000),
+        };
+        unsafe { timerfd_settime(fd, 0, &new, core::ptr::null_mut()) }.unwrap();
+
+        let mut curr = ITimerSpec::default();
+        unsafe { timerfd_gettime(fd, &mut curr) }.unwrap();
+        assert_eq!(curr.interval, new.interval);
+        assert!(curr.value.as_nanoseconds() > 0);
+        assert!(curr.value.as_nanoseconds() <= new.value.as_nanoseconds());
+
+        unsafe { close(fd) }.unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+use std::hint::black_box;
+
+use syscalls::Errno;
+
+use crate::lowlevel::mman::{mlockall, munlockall, MCL_CURRENT, MCL_FUTURE};
+
+/// Smallest page size of the supported architectures. Writing one byte every
+/// `PAGE_SIZE` bytes touches every page, even if the actual page size is larger.
+const PAGE_SIZE: usize = 4096;
+
+/// Locks all current and future pages of the process in RAM with
+/// `mlockall(MCL_CURRENT | MCL_FUTURE)`, so that they cannot be paged out.
+/// Requires `CAP_IPC_LOCK` or a sufficient `RLIMIT_MEMLOCK`.
+pub fn lock_all_memory() -> Result<(), Errno> {
+    unsafe { mlockall(MCL_CURRENT | MCL_FUTURE) }.and(Ok(()))
+}
+
+/// Undoes `lock_all_memory()`.
+pub fn unlock_all_memory() -> Result<(), Errno> {
+    unsafe { munlockall() }.and(Ok(()))
+}
+
+/// Touches `size` bytes of stack below the current stack frame, so that the
+/// kernel maps these pages before the real-time section needs them.
+///
+/// Page faults in a real-time section cause latency spikes. The usual pattern at
+/// startup is to lock all current and future memory with `lock_all_memory()`
+/// and then call `prefault_stack()` with the
+/// maximum stack depth of the real-time code. Locked pages stay mapped, so the
+/// section does not fault on its stack later on. Without locking, the kernel may
+/// reclaim the pages again.
+///
+/// `size` must be smaller than the stack of the thread.
+pub fn prefault_stack(size: usize) {
+    #[inline(never)]
+    fn touch(remaining: usize) {
+        let mut page = [0u8; PAGE_SIZE];
+        black_box(&mut page);
+        if remaining > PAGE_SIZE {
+            touch(remaining - PAGE_SIZE);
+        }
+        // Keeps the frame alive during the recursive call.
+        black_box(&page);
+    }
+    touch(size);
+}
+
+/// Allocates a heap buffer of `size` bytes, writes to each of its pages and
+/// frees it again, so that the allocator can serve later allocations of the
+/// real-time section from memory that is already mapped.
+///
+/// As with `prefault_stack()`, lock the memory with `lock_all_memory()` first. Note that
+/// the allocator may return large freed blocks to the kernel, e.g. glibc unmaps
+/// blocks above `M_MMAP_THRESHOLD` and trims the heap above `M_TRIM_THRESHOLD`;
+/// disable this with mallopt(3) for the prefaulting to be effective.
+pub fn prefault_heap(size: usize) {
+    let mut buffer: Vec<u8> = Vec::with_capacity(size);
+    let spare = buffer.spare_capacity_mut();
+    for offset in (0..size).step_by(PAGE_SIZE) {
+        spare[offset].write(1);
+    }
+    black_box(&mut buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_all_memory() {
+        // Memory locking applies to the whole process, so lock in a forked child
+        // to keep it away from the other tests of this binary.
+        match unsafe { libc::fork() } {
+            0 => {
+                let ok = lock_all_memory().is_ok() && unlock_all_memory().is_ok();
+                unsafe { libc::_exit(if ok { 0 } else { 1 }) }
+            }
+            child => {
+                assert!(child > 0);
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(libc::WEXITSTATUS(status), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefault() {
+        prefault_stack(256 * 1024);
+        prefault_heap(4 * 1024 * 1024);
+        prefault_heap(0);
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{AtomicI32, Ordering},
+};
+
+use syscalls::Errno;
+
+use crate::{
+    get_time,
+    lowlevel::{
+        futex::{futex_lock_pi_private, futex_unlock_pi_private},
+        sched::gettid,
+    },
+    ClockId, TimeSpec,
+};
+
+thread_local! {
+    static TID: i32 = unsafe { gettid() }.expect("gettid() cannot fail") as i32;
+}
+
+/// A mutex with priority inheritance, based on PI futexes.
+///
+/// While a thread blocks on the mutex, the owner runs with the priority of the
+/// blocked thread, so a low priority owner cannot be preempted indefinitely by
+/// medium priority threads (priority inversion). Priority inheritance only has
+/// an effect if the threads use a real-time policy like `SCHED_FIFO`; for
+/// `SCHED_OTHER` threads it behaves like a regular mutex.
+///
+/// The lock is not poisoned if a thread panics while holding it.
+pub struct PiMutex<T: ?Sized> {
+    /// 0 if unlocked, otherwise the TID of the owner, possibly combined with
+    /// `FUTEX_WAITERS` by the kernel.
+    futex: AtomicI32,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for PiMutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for PiMutex<T> {}
+
+impl<T> PiMutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            futex: AtomicI32::new(0),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> PiMutex<T> {
+    /// Acquires the mutex, blocking until it is available.
+    ///
+    /// Panics if the mutex is already held by the calling thread.
+    pub fn lock(&self) -> PiMutexGuard<'_, T> {
+        self.lock_slow(None)
+            .unwrap_or_else(|err| panic!("FUTEX_LOCK_PI failed: {err}"))
+            .expect("no timeout given")
+    }
+
+    /// Acquires the mutex if it is available without blocking.
+    pub fn try_lock(&self) -> Option<PiMutexGuard<'_, T>> {
+        let tid = TID.with(|tid| *tid);
+        self.futex
+            .compare_exchange(0, tid, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| PiMutexGuard::new(self))
+    }
+
+    /// Acquires the mutex, blocking at most until `ClockMonotonic` reaches
+    /// `deadline`. Returns `None` if the deadline passed.
+    ///
+    /// Waiting on `ClockMonotonic` needs `FUTEX_LOCK_PI2` from Linux 5.14. On
+    /// older kernels the deadline is converted to `ClockRealtime` once, so a
+    /// wall-clock change during the wait shifts it.
+    ///
+    /// Panics if the mutex is already held by the calling thread.
+    pub fn try_lock_until(&self, deadline: TimeSpec) -> Option<PiMutexGuard<'_, T>> {
+        self.lock_slow(Some(deadline))
+            .unwrap_or_else(|err| panic!("FUTEX_LOCK_PI failed: {err}"))
+    }
+
+    /// Blocks on the futex until it is acquired or `deadline` on
+    /// `ClockMonotonic` passes.
+    fn lock_slow(&self, deadline: Option<TimeSpec>) -> Result<Option<PiMutexGuard<'_, T>>, Errno> {
+        if let Some(guard) = self.try_lock() {
+            return Ok(Some(guard));
+        }
+        // Without a timeout the clock does not matter, so the plain
+        // `FUTEX_LOCK_PI` works on every kernel.
+        let (mut timeout, mut clockrt) = match deadline {
+            Some(deadline) => (deadline, false),
+            None => (TimeSpec::zeroed(), true),
+        };
+        loop {
+            let timeout_ptr = match deadline {
+                Some(_) => &timeout as *const TimeSpec,
+                None => ptr::null(),
+            };
+            match unsafe { futex_lock_pi_private(self.futex.as_ptr(), timeout_ptr, clockrt) } {
+                Ok(()) => return Ok(Some(PiMutexGuard::new(self))),
+                Err(Errno::EINTR) => continue,
+                Err(Errno::ENOSYS) if !clockrt => {
+                    let now = get_time(ClockId::ClockMonotonic)?;
+                    timeout = get_time(ClockId::ClockRealtime)? + (timeout - now);
+                    clockrt = true;
+                }
+                Err(Errno::ETIMEDOUT) => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for PiMutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for PiMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("PiMutex");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+/// Releases the `PiMutex` when dropped. A PI futex must be released by its
+/// owner, so the guard cannot be sent to another thread.
+pub struct PiMutexGuard<'a, T: ?Sized> {
+    mutex: &'a PiMutex<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for PiMutexGuard<'_, T> {}
+
+impl<'a, T: ?Sized> PiMutexGuard<'a, T> {
+    fn new(mutex: &'a PiMutex<T>) -> Self {
+        Self {
+            mutex,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for PiMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for PiMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for PiMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let tid = TID.with(|tid| *tid);
+        // If there are waiters, the kernel has to hand the lock over.
+        if self
+            .mutex
+            .futex
+            .compare_exchange(tid, 0, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            unsafe { futex_unlock_pi_private(self.mutex.futex.as_ptr()) }
+                .expect("FUTEX_UNLOCK_PI failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pi_mutex() {
+        let mutex = Arc::new(PiMutex::new(0u64));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mutex = mutex.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        *mutex.lock() += 1;
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(*mutex.lock(), 4000);
+    }
+
+    #[test]
+    fn test_pi_mutex_try_lock() {
+        let mutex = Arc::new(PiMutex::new(()));
+        let guard = mutex.lock();
+        assert!(mutex.try_lock().is_none());
+        let other = mutex.clone();
+        std::thread::spawn(move || {
+            assert!(other.try_lock().is_none());
+            let deadline =
+                get_time(ClockId::ClockMonotonic).unwrap() + TimeSpec::nanoseconds(1_000_000);
+            assert!(other.try_lock_until(deadline).is_none());
+        })
+        .join()
+        .unwrap();
+        drop(guard);
+        assert!(mutex.try_lock().is_some());
+        let deadline = get_time(ClockId::ClockMonotonic).unwrap();
+        assert!(mutex.try_lock_until(deadline).is_some());
+    }
+}
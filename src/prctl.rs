@@ -0,0 +1,79 @@
+use std::fs;
+
+use syscalls::Errno;
+
+use crate::{
+    lowlevel::prctl::{
+        prctl, PR_GET_DUMPABLE, PR_GET_TIMERSLACK, PR_SET_DUMPABLE, PR_SET_TIMERSLACK,
+    },
+    system::read_value,
+    Pid, SchedError,
+};
+
+/// Sets the timer slack of the calling thread in nanoseconds. The kernel may delay
+/// timer expirations by up to this amount to group wakeups and save power.
+///
+/// A value of 0 resets the slack to the thread's default value (usually 50 µs);
+/// use 1 for the most precise timers. The slack is ignored for threads with a
+/// real-time policy.
+pub fn set_timer_slack(ns: u64) -> Result<(), Errno> {
+    unsafe { prctl(PR_SET_TIMERSLACK, ns as usize, 0, 0, 0) }.and(Ok(()))
+}
+
+/// Returns the timer slack of the calling thread in nanoseconds.
+pub fn get_timer_slack() -> Result<u64, Errno> {
+    unsafe { prctl(PR_GET_TIMERSLACK, 0, 0, 0, 0) }.map(|ns| ns as u64)
+}
+
+/// Timer slack of any thread: for the calling thread it is available through
+/// `prctl()`, for other threads it is read from `/proc/[pid]/timerslack_ns`.
+pub(crate) fn get_timer_slack_of(pid: Pid) -> Result<u64, SchedError> {
+    if pid == Pid::this() {
+        Ok(get_timer_slack()?)
+    } else {
+        Ok(read_value(&pid.proc_path("timerslack_ns"))?)
+    }
+}
+
+/// Counterpart of `get_timer_slack_of()`. Writing the slack of other threads
+/// requires `CAP_SYS_NICE`.
+pub(crate) fn set_timer_slack_of(pid: Pid, ns: u64) -> Result<(), SchedError> {
+    if pid == Pid::this() {
+        Ok(set_timer_slack(ns)?)
+    } else {
+        Ok(fs::write(pid.proc_path("timerslack_ns"), ns.to_string())?)
+    }
+}
+
+/// Sets the "dumpable" attribute of the calling process, which determines
+/// whether core dumps are produced and whether the process can be attached with
+/// ptrace(2). Processes that changed their credentials, e.g. RT applications
+/// started as set-user-ID programs, are not dumpable by default.
+pub fn set_dumpable(dumpable: bool) -> Result<(), Errno> {
+    unsafe { prctl(PR_SET_DUMPABLE, dumpable as usize, 0, 0, 0) }.and(Ok(()))
+}
+
+/// Returns the "dumpable" attribute of the calling process.
+pub fn get_dumpable() -> Result<bool, Errno> {
+    unsafe { prctl(PR_GET_DUMPABLE, 0, 0, 0, 0) }.map(|dumpable| dumpable != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_slack() {
+        set_timer_slack(1).unwrap();
+        assert_eq!(get_timer_slack().unwrap(), 1);
+        set_timer_slack(100_000).unwrap();
+        assert_eq!(get_timer_slack().unwrap(), 100_000);
+    }
+
+    #[test]
+    fn test_dumpable() {
+        let dumpable = get_dumpable().unwrap();
+        set_dumpable(dumpable).unwrap();
+        assert_eq!(get_dumpable().unwrap(), dumpable);
+    }
+}
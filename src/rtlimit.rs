@@ -0,0 +1,143 @@
+use std::{fs, io};
+
+use crate::{online_cpu_count, SchedError};
+
+const SCHED_RT_PERIOD_US: &str = "/proc/sys/kernel/sched_rt_period_us";
+const SCHED_RT_RUNTIME_US: &str = "/proc/sys/kernel/sched_rt_runtime_us";
+
+/// The kernel reports `-1` for `sched_rt_runtime_us` if real-time tasks are not
+/// throttled at all. This value is used to represent it. It is only valid for
+/// the runtime; the kernel rejects `-1` for `sched_rt_period_us`.
+pub const RT_UNLIMITED: u64 = u64::MAX;
+
+fn read_sysctl(path: &str) -> Result<u64, io::Error> {
+    let value = fs::read_to_string(path)?
+        .trim()
+        .parse::<i64>()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    if value < 0 {
+        Ok(RT_UNLIMITED)
+    } else {
+        Ok(value as u64)
+    }
+}
+
+fn write_sysctl(path: &str, value: u64) -> Result<(), io::Error> {
+    if value == RT_UNLIMITED {
+        return fs::write(path, "-1");
+    }
+    let value =
+        i64::try_from(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    fs::write(path, value.to_string())
+}
+
+/// Reads `/proc/sys/kernel/sched_rt_period_us`, the period in microseconds that
+/// is considered 100% of the CPU bandwidth for real-time tasks.
+pub fn get_rt_period_us() -> Result<u64, io::Error> {
+    read_sysctl(SCHED_RT_PERIOD_US)
+}
+
+/// Reads `/proc/sys/kernel/sched_rt_runtime_us`, the part of each period in
+/// microseconds that can be used by real-time tasks. Returns [`RT_UNLIMITED`] if
+/// real-time tasks are not throttled.
+pub fn get_rt_runtime_us() -> Result<u64, io::Error> {
+    read_sysctl(SCHED_RT_RUNTIME_US)
+}
+
+/// Writes `/proc/sys/kernel/sched_rt_period_us`. Requires root.
+///
+/// Fails with `io::ErrorKind::InvalidInput` for [`RT_UNLIMITED`], which is only
+/// valid for the runtime, see `set_rt_runtime_us()`.
+pub fn set_rt_period_us(period_us: u64) -> Result<(), io::Error> {
+    if period_us == RT_UNLIMITED {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the real-time period cannot be unlimited",
+        ));
+    }
+    write_sysctl(SCHED_RT_PERIOD_US, period_us)
+}
+
+/// Writes `/proc/sys/kernel/sched_rt_runtime_us`. Pass [`RT_UNLIMITED`] to disable
+/// throttling of real-time tasks. Requires root.
+pub fn set_rt_runtime_us(runtime_us: u64) -> Result<(), io::Error> {
+    write_sysctl(SCHED_RT_RUNTIME_US, runtime_us)
+}
+
+/// Sanity-checks deadline parameters against the system-wide real-time bandwidth
+/// before they are handed to the kernel.
+///
+/// The kernel admits a `SCHED_DEADLINE` task if its bandwidth plus the bandwidth
+/// of all existing deadline tasks fits into `cpus * rt_runtime / rt_period` of the
+/// root domain. The bandwidth already held by other deadline tasks is not visible
+/// from user space, so this only checks the new task on its own against the
+/// capacity of all online CPUs. Parameters that fail the check are rejected with
+/// [`SchedError::BandwidthExceeded`] instead of a bare `EBUSY`, but passing it does
+/// not guarantee that `sched_setattr()` succeeds.
+pub struct DeadlineBandwidthGuard;
+
+impl DeadlineBandwidthGuard {
+    /// Reads `sched_rt_period_us`, `sched_rt_runtime_us` and the number of online
+    /// CPUs and returns [`SchedError::BandwidthExceeded`] if a task with the given
+    /// runtime and period alone would use more bandwidth than real-time tasks may
+    /// use on all of them together.
+    ///
+    /// This is only an upper bound: bandwidth held by other deadline tasks is not
+    /// taken into account, so `sched_setattr()` may still fail with `EBUSY`.
+    pub fn check(runtime_ns: u64, period_ns: u64) -> Result<(), SchedError> {
+        let rt_period_us = get_rt_period_us()?;
+        let rt_runtime_us = get_rt_runtime_us()?;
+        let cpus = online_cpu_count()?;
+        check_bandwidth(runtime_ns, period_ns, rt_runtime_us, rt_period_us, cpus)
+    }
+}
+
+fn check_bandwidth(
+    runtime_ns: u64,
+    period_ns: u64,
+    rt_runtime_us: u64,
+    rt_period_us: u64,
+    cpus: usize,
+) -> Result<(), SchedError> {
+    if rt_runtime_us == RT_UNLIMITED {
+        return Ok(());
+    }
+    // runtime / period <= cpus * rt_runtime / rt_period, without the division
+    if runtime_ns as u128 * rt_period_us as u128
+        <= cpus as u128 * rt_runtime_us as u128 * period_ns as u128
+    {
+        Ok(())
+    } else {
+        Err(SchedError::BandwidthExceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rt_bandwidth() {
+        let period = get_rt_period_us().unwrap();
+        let runtime = get_rt_runtime_us().unwrap();
+        assert!(period > 0);
+        assert!(runtime == RT_UNLIMITED || runtime <= period);
+        assert_eq!(
+            set_rt_period_us(RT_UNLIMITED).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_check_bandwidth() {
+        assert!(check_bandwidth(50_000, 1_000_000, 950_000, 1_000_000, 1).is_ok());
+        assert!(check_bandwidth(950_000, 1_000_000, 950_000, 1_000_000, 1).is_ok());
+        assert!(matches!(
+            check_bandwidth(960_000, 1_000_000, 950_000, 1_000_000, 1),
+            Err(SchedError::BandwidthExceeded)
+        ));
+        assert!(check_bandwidth(970_000, 1_000_000, 950_000, 1_000_000, 2).is_ok());
+        assert!(check_bandwidth(1_000_000, 1_000_000, RT_UNLIMITED, 1_000_000, 1).is_ok());
+        DeadlineBandwidthGuard::check(50_000, 1_000_000).unwrap();
+    }
+}
@@ -1,16 +1,27 @@
-use crate::lowlevel::sched::{
-    self, pid_t, sched_get_affinity, sched_get_attr, sched_set_affinity, sched_set_attr, CpuSet,
-    SchedAttr, SCHED_BATCH, SCHED_DEADLINE, SCHED_EXT, SCHED_FIFO, SCHED_IDLE, SCHED_NORMAL,
-    SCHED_RR,
+use crate::{
+    lowlevel::sched::{
+        self, pid_t, sched_get_affinity, sched_get_attr, sched_getparam, sched_getscheduler,
+        sched_set_affinity, sched_set_attr, sched_setparam, sched_setscheduler, CpuSet, SchedAttr,
+        SchedParam, SCHED_BATCH, SCHED_DEADLINE, SCHED_EXT, SCHED_FIFO, SCHED_IDLE, SCHED_NORMAL,
+        SCHED_RESET_ON_FORK, SCHED_RR,
+    },
+    SchedError,
 };
 use bitflags::bitflags;
-use std::{ffi::c_int, fmt::Error, mem};
+use std::{
+    borrow::Borrow,
+    ffi::c_int,
+    fmt::{self, Error},
+    fs, io, mem,
+    sync::Mutex,
+};
 use syscalls::Errno;
 
 /// Currently, Linux supports the scheduling policies defined in this enum.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
 pub enum Policy {
     ///The standard round-robin time-sharing policy
+    #[default]
     Normal,
     /// For "batch" style execution of processes
     Batch,
@@ -44,7 +55,19 @@ impl Policy {
             Policy::Ext => SCHED_EXT,
         }
     }
+    /// Converts a raw `SCHED_*` constant. Prefer `Policy::try_from()`, which
+    /// reports the unknown value.
     pub fn from_raw(raw: u32) -> Result<Policy, Error> {
+        Policy::try_from(raw).map_err(|_| Error)
+    }
+}
+
+impl TryFrom<u32> for Policy {
+    type Error = SchedError;
+
+    /// Converts a raw `SCHED_*` constant, failing with
+    /// `SchedError::UnknownPolicy` for values this crate does not know.
+    fn try_from(raw: u32) -> Result<Self, SchedError> {
         match raw {
             SCHED_NORMAL => Ok(Policy::Normal),
             SCHED_FIFO => Ok(Policy::Fifo),
@@ -53,13 +76,14 @@ impl Policy {
             SCHED_IDLE => Ok(Policy::Idle),
             SCHED_DEADLINE => Ok(Policy::Deadline),
             SCHED_EXT => Ok(Policy::Ext),
-            _ => Err(Error),
+            _ => Err(SchedError::UnknownPolicy(raw)),
         }
     }
 }
 
 bitflags! {
     /// These flags control the scheduling behavior:
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct SchedFlags: std::ffi::c_short {
         /// Children created by fork(2) do not inherit
         /// privileged scheduling policies. See sched(7) for
@@ -83,6 +107,12 @@ bitflags! {
         /// application has no way of knowing which thread
         /// overran.
         const SCHED_FLAG_DL_OVERRUN = 0x04;
+        /// Keeps the current scheduling parameters of the thread, i.e. the
+        /// nice value, the static priority and the deadline runtime, deadline
+        /// and period. The corresponding fields are ignored. Note that the
+        /// kernel stores `SCHED_FLAG_RECLAIM` and `SCHED_FLAG_DL_OVERRUN` with
+        /// the deadline parameters, so they are kept as well; use
+        /// `update_deadline_flags()` to change them.
         const SCHED_FLAG_KEEP_PARAMS = 0x10;
         /// These flags indicate that the sched_util_min or
         /// sched_util_max fields, respectively, are present,
@@ -99,7 +129,17 @@ bitflags! {
     }
 }
 
+impl Default for SchedFlags {
+    /// Returns `SchedFlags::empty()`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 ///Structure containing the scheduling policy and attributes for the specified thread.
+///
+/// The default is `SCHED_OTHER` with all other fields zero.
+#[derive(Debug, Clone, Default)]
 pub struct Attributes {
     /// This field specifies the scheduling policy, as one of the values of the enum.
     pub policy: Policy,
@@ -144,6 +184,278 @@ pub struct Attributes {
     pub sched_util_max: u32,
 }
 
+impl Attributes {
+    fn with_policy(policy: Policy) -> Attributes {
+        Attributes {
+            policy,
+            flags: SchedFlags::empty(),
+            nice: 0,
+            priority: 0,
+            runtime_ns: 0,
+            deadline_ns: 0,
+            period_ns: 0,
+            sched_util_min: 0,
+            sched_util_max: 0,
+        }
+    }
+
+    /// Attributes for `SCHED_FIFO` with the static `priority`.
+    pub fn for_fifo(priority: u32) -> Result<Attributes, SchedError> {
+        let attr = Attributes {
+            priority,
+            ..Attributes::with_policy(Policy::Fifo)
+        };
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Attributes for `SCHED_RR` with the static `priority`.
+    pub fn for_rr(priority: u32) -> Result<Attributes, SchedError> {
+        let attr = Attributes {
+            priority,
+            ..Attributes::with_policy(Policy::RoundRobin)
+        };
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Attributes for `SCHED_DEADLINE`. All values are in nanoseconds.
+    pub fn for_deadline(
+        runtime_ns: u64,
+        deadline_ns: u64,
+        period_ns: u64,
+    ) -> Result<Attributes, SchedError> {
+        let attr = Attributes {
+            runtime_ns,
+            deadline_ns,
+            period_ns,
+            ..Attributes::with_policy(Policy::Deadline)
+        };
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Attributes for `SCHED_OTHER` with the given `nice` value.
+    pub fn for_normal(nice: i32) -> Result<Attributes, SchedError> {
+        let attr = Attributes {
+            nice,
+            ..Attributes::with_policy(Policy::Normal)
+        };
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Attributes for `SCHED_BATCH` with the given `nice` value.
+    pub fn for_batch(nice: i32) -> Result<Attributes, SchedError> {
+        let attr = Attributes {
+            nice,
+            ..Attributes::with_policy(Policy::Batch)
+        };
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Attributes for `SCHED_IDLE`.
+    pub fn for_idle() -> Result<Attributes, SchedError> {
+        let attr = Attributes::with_policy(Policy::Idle);
+        attr.validate()?;
+        Ok(attr)
+    }
+
+    /// Adds `SCHED_FLAG_RESET_ON_FORK`.
+    pub fn with_reset_on_fork(mut self) -> Self {
+        self.flags |= SchedFlags::SCHED_FLAG_RESET_ON_FORK;
+        self
+    }
+
+    /// Adds `SCHED_FLAG_DL_OVERRUN`.
+    pub fn with_dl_overrun(mut self) -> Self {
+        self.flags |= SchedFlags::SCHED_FLAG_DL_OVERRUN;
+        self
+    }
+
+    /// Adds `SCHED_FLAG_RECLAIM`.
+    pub fn with_reclaim(mut self) -> Self {
+        self.flags |= SchedFlags::SCHED_FLAG_RECLAIM;
+        self
+    }
+
+    /// Sets `sched_util_min` and `sched_util_max` and adds
+    /// `SCHED_FLAG_UTIL_CLAMP_MIN` and `SCHED_FLAG_UTIL_CLAMP_MAX`.
+    pub fn with_util_clamp(mut self, min: u32, max: u32) -> Self {
+        self.sched_util_min = min;
+        self.sched_util_max = max;
+        self.flags |= SchedFlags::SCHED_FLAG_UTIL_CLAMP_MIN | SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX;
+        self
+    }
+
+    /// Checks the attributes for the mistakes `sched_setattr()` would reject with
+    /// a plain `EINVAL`:
+    ///
+    /// * the priority must be in the range of the real-time policies, and 0 for
+    ///   all other policies,
+    /// * the nice value must be in the range -20 to 19,
+    /// * the deadline parameters must satisfy
+    ///   `1024 <= runtime_ns <= deadline_ns <= period_ns`, where a zero period
+    ///   means the period equals the deadline,
+    /// * the utilization clamps must not exceed 1024.
+    pub fn validate(&self) -> Result<(), SchedError> {
+        match self.policy {
+            Policy::Fifo | Policy::RoundRobin => {
+                clamp_to_valid_priority(self.policy, self.priority)?;
+            }
+            _ if self.priority != 0 => {
+                return Err(SchedError::PriorityOutOfRange {
+                    priority: self.priority,
+                    range: PriorityRange { min: 0, max: 0 },
+                })
+            }
+            _ => {}
+        }
+        if !(-20..=19).contains(&self.nice) {
+            return Err(SchedError::InvalidAttributes(
+                "nice must be in the range -20 to 19",
+            ));
+        }
+        if self.policy == Policy::Deadline {
+            let period_ns = if self.period_ns == 0 {
+                self.deadline_ns
+            } else {
+                self.period_ns
+            };
+            if self.runtime_ns < 1024 {
+                return Err(SchedError::InvalidAttributes(
+                    "runtime_ns must be at least 1024",
+                ));
+            }
+            if !(self.runtime_ns <= self.deadline_ns && self.deadline_ns <= period_ns) {
+                return Err(SchedError::InvalidAttributes(
+                    "runtime_ns <= deadline_ns <= period_ns does not hold",
+                ));
+            }
+        }
+        let clamp_invalid = |flag: SchedFlags, value: u32| {
+            self.flags.contains(flag) && value > 1024 && value != u32::MAX
+        };
+        if clamp_invalid(SchedFlags::SCHED_FLAG_UTIL_CLAMP_MIN, self.sched_util_min)
+            || clamp_invalid(SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX, self.sched_util_max)
+        {
+            return Err(SchedError::InvalidAttributes(
+                "utilization clamps must not exceed 1024",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns `base` with every field replaced that is set in `override_with`.
+    ///
+    /// A field counts as unset if it has its zero value: `Policy::Normal`, empty
+    /// `flags`, or `0`. Consequently, this cannot be used to switch back to
+    /// `Policy::Normal` or to reset a field to zero.
+    pub fn merge(base: &Attributes, override_with: &Attributes) -> Attributes {
+        fn pick<T: PartialEq + Copy>(base: T, override_with: T, unset: T) -> T {
+            if override_with == unset {
+                base
+            } else {
+                override_with
+            }
+        }
+        Attributes {
+            policy: pick(base.policy, override_with.policy, Policy::Normal),
+            flags: pick(base.flags, override_with.flags, SchedFlags::empty()),
+            nice: pick(base.nice, override_with.nice, 0),
+            priority: pick(base.priority, override_with.priority, 0),
+            runtime_ns: pick(base.runtime_ns, override_with.runtime_ns, 0),
+            deadline_ns: pick(base.deadline_ns, override_with.deadline_ns, 0),
+            period_ns: pick(base.period_ns, override_with.period_ns, 0),
+            sched_util_min: pick(base.sched_util_min, override_with.sched_util_min, 0),
+            sched_util_max: pick(base.sched_util_max, override_with.sched_util_max, 0),
+        }
+    }
+
+    /// Returns the names of the fields that differ between `a` and `b`, e.g.
+    /// `["policy", "priority"]`.
+    pub fn diff(a: &Attributes, b: &Attributes) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if a.policy != b.policy {
+            changed.push("policy");
+        }
+        if a.flags != b.flags {
+            changed.push("flags");
+        }
+        if a.nice != b.nice {
+            changed.push("nice");
+        }
+        if a.priority != b.priority {
+            changed.push("priority");
+        }
+        if a.runtime_ns != b.runtime_ns {
+            changed.push("runtime_ns");
+        }
+        if a.deadline_ns != b.deadline_ns {
+            changed.push("deadline_ns");
+        }
+        if a.period_ns != b.period_ns {
+            changed.push("period_ns");
+        }
+        if a.sched_util_min != b.sched_util_min {
+            changed.push("sched_util_min");
+        }
+        if a.sched_util_max != b.sched_util_max {
+            changed.push("sched_util_max");
+        }
+        changed
+    }
+}
+
+/// Writes a duration in the largest unit that represents it exactly, e.g.
+/// `500µs` for 500000 ns.
+fn write_duration_ns(f: &mut fmt::Formatter<'_>, ns: u64) -> fmt::Result {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "s"), (1_000_000, "ms"), (1_000, "µs")];
+    for (scale, unit) in UNITS {
+        let (value, rest) = (ns / scale, ns % scale);
+        if value != 0 && rest == 0 {
+            return write!(f, "{value}{unit}");
+        }
+    }
+    write!(f, "{ns}ns")
+}
+
+/// Shows a one-line summary with only the fields that are relevant for the
+/// policy, e.g. `SCHED_DEADLINE runtime=500µs deadline=1ms period=10ms`.
+impl fmt::Display for Attributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.policy {
+            Policy::Normal => write!(f, "SCHED_OTHER nice={}", self.nice)?,
+            Policy::Batch => write!(f, "SCHED_BATCH nice={}", self.nice)?,
+            Policy::Idle => write!(f, "SCHED_IDLE")?,
+            Policy::Ext => write!(f, "SCHED_EXT nice={}", self.nice)?,
+            Policy::Fifo => write!(f, "SCHED_FIFO priority={}", self.priority)?,
+            Policy::RoundRobin => write!(f, "SCHED_RR priority={}", self.priority)?,
+            Policy::Deadline => {
+                write!(f, "SCHED_DEADLINE runtime=")?;
+                write_duration_ns(f, self.runtime_ns)?;
+                write!(f, " deadline=")?;
+                write_duration_ns(f, self.deadline_ns)?;
+                write!(f, " period=")?;
+                write_duration_ns(f, self.period_ns)?;
+            }
+        }
+        if self.flags.contains(SchedFlags::SCHED_FLAG_UTIL_CLAMP_MIN) {
+            write!(f, " util_min={}", self.sched_util_min)?;
+        }
+        if self.flags.contains(SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX) {
+            write!(f, " util_max={}", self.sched_util_max)?;
+        }
+        if !self.flags.is_empty() {
+            write!(f, " flags=")?;
+            bitflags::parser::to_writer(&self.flags, &mut *f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Pid(pid_t);
 impl Pid {
     pub fn as_raw(&self) -> pid_t {
@@ -155,11 +467,84 @@ impl Pid {
     pub fn new(pid: pid_t) -> Self {
         Self(pid)
     }
+    /// The thread ID of the calling thread, as returned by `gettid()`. Unlike
+    /// `this()`, the ID stays valid when passed to another thread.
+    pub fn of_current_thread() -> Self {
+        let tid = unsafe { sched::gettid() }.expect("gettid() cannot fail");
+        Self(tid as pid_t)
+    }
+    /// The thread ID of the main thread of the calling process, which equals the
+    /// process ID returned by `getpid()`.
+    pub fn of_main_thread() -> Self {
+        let pid = unsafe { sched::getpid() }.expect("getpid() cannot fail");
+        Self(pid as pid_t)
+    }
+    /// Path of `file` in the procfs directory of the thread. If `self` is zero,
+    /// the directory of the calling thread is used.
+    pub(crate) fn proc_path(&self, file: &str) -> String {
+        match self.0 {
+            0 => format!("/proc/thread-self/{file}"),
+            pid => format!("/proc/{pid}/{file}"),
+        }
+    }
+    /// Lists the IDs of all threads of the process whose ID is specified in
+    /// `self`, read from `/proc/[pid]/task`. If `self` is zero, the threads of
+    /// the calling process are listed.
+    pub fn all_threads(&self) -> Result<Vec<Pid>, io::Error> {
+        let path = match self.0 {
+            0 => "/proc/self/task".to_string(),
+            pid => format!("/proc/{pid}/task"),
+        };
+        let mut threads = Vec::new();
+        for entry in fs::read_dir(path)? {
+            if let Some(tid) = entry?.file_name().to_str().and_then(|n| n.parse().ok()) {
+                threads.push(Pid(tid));
+            }
+        }
+        Ok(threads)
+    }
+}
+
+impl AsRef<pid_t> for Pid {
+    fn as_ref(&self) -> &pid_t {
+        &self.0
+    }
+}
+
+impl Borrow<pid_t> for Pid {
+    fn borrow(&self) -> &pid_t {
+        &self.0
+    }
 }
 
 /// The `get_attr()` function wraps the `sched_getattr()` system call and fetches the scheduling policy and
 /// the associated attributes for the thread whose ID is specified in pid.
 pub fn get_attr(pid: Pid) -> Result<Attributes, Errno> {
+    let attr = read_attr(pid)?;
+    Ok(Attributes {
+        policy: Policy::from_raw(attr.sched_policy).unwrap(),
+        ..attributes_from_raw(&attr)
+    })
+}
+
+/// Like `get_attr()`, but reports an unknown policy as
+/// `SchedError::UnknownPolicy` instead of panicking.
+///
+/// The kernel accepts any buffer at least as large as the first version of
+/// `struct sched_attr` and copies as much of its own struct as fits. Kernels
+/// before 5.3 lack the utilization clamping fields, which are then returned
+/// as 0.
+pub fn get_attr_compat(pid: Pid) -> Result<Attributes, SchedError> {
+    let attr = read_attr(pid)?;
+    Ok(Attributes {
+        policy: Policy::try_from(attr.sched_policy)?,
+        ..attributes_from_raw(&attr)
+    })
+}
+
+/// Calls `sched_getattr()` with a zeroed buffer, so fields the kernel does not
+/// know stay 0.
+fn read_attr(pid: Pid) -> Result<SchedAttr, Errno> {
     let mut attr = SchedAttr {
         size: 0,
         sched_policy: 0,
@@ -172,31 +557,23 @@ pub fn get_attr(pid: Pid) -> Result<Attributes, Errno> {
         sched_util_min: 0,
         sched_util_max: 0,
     };
+    let size = mem::size_of::<SchedAttr>() as u32;
+    unsafe { sched_get_attr(pid.as_raw(), &mut attr, size, 0) }.and(Ok(attr))
+}
 
-    let ret = unsafe {
-        sched_get_attr(
-            pid.as_raw(),
-            &mut attr,
-            mem::size_of::<SchedAttr>() as u32,
-            0,
-        )
-    };
-    match ret {
-        Ok(_) => {
-            let a = Attributes {
-                policy: Policy::from_raw(attr.sched_policy).unwrap(),
-                flags: SchedFlags::from_bits_truncate(attr.sched_flags as i16),
-                nice: attr.sched_nice,
-                priority: attr.sched_priority,
-                deadline_ns: attr.sched_deadline,
-                period_ns: attr.sched_period,
-                runtime_ns: attr.sched_runtime,
-                sched_util_min: attr.sched_util_min,
-                sched_util_max: attr.sched_util_max,
-            };
-            Ok(a)
-        }
-        Err(err) => Err(err),
+/// Converts everything but the policy, which callers convert with their own
+/// error handling.
+fn attributes_from_raw(attr: &SchedAttr) -> Attributes {
+    Attributes {
+        policy: Policy::Normal,
+        flags: SchedFlags::from_bits_truncate(attr.sched_flags as i16),
+        nice: attr.sched_nice,
+        priority: attr.sched_priority,
+        deadline_ns: attr.sched_deadline,
+        period_ns: attr.sched_period,
+        runtime_ns: attr.sched_runtime,
+        sched_util_min: attr.sched_util_min,
+        sched_util_max: attr.sched_util_max,
     }
 }
 
@@ -260,6 +637,25 @@ pub fn set_idle(pid: Pid) -> Result<(), Errno> {
     };
     set_attr(pid, att_batch)
 }
+/// Sets `SCHED_EXT`, the policy of schedulers implemented as BPF programs. As
+/// long as no BPF scheduler is loaded, the thread is scheduled like
+/// `SCHED_OTHER`. Fails if the kernel does not support `SCHED_EXT`, with an
+/// errno that depends on the kernel version and configuration, see
+/// `is_ext_available()`.
+pub fn set_ext(pid: Pid) -> Result<(), SchedError> {
+    set_attr(pid, Attributes::with_policy(Policy::Ext))?;
+    Ok(())
+}
+
+/// Returns `true` if the kernel supports `SCHED_EXT`, which was added in
+/// Linux 6.12 and needs `CONFIG_SCHED_CLASS_EXT`.
+///
+/// Kernels since 6.12 know the policy number even without the config option,
+/// so besides `get_priority_min()` this checks for `/sys/kernel/sched_ext`.
+pub fn is_ext_available() -> bool {
+    get_priority_min(Policy::Ext).is_ok() && fs::metadata("/sys/kernel/sched_ext").is_ok()
+}
+
 pub fn set_fifo(pid: Pid, priority: u32) -> Result<(), Errno> {
     let att_batch = Attributes {
         policy: Policy::Fifo,
@@ -294,28 +690,164 @@ pub fn set_deadline(
     period_ns: u64,
     runtime_ns: u64,
 ) -> Result<(), Errno> {
-    if !((runtime_ns <= deadline_ns) && (deadline_ns <= period_ns)) {
-        println!("Error: params are not sched_runtime <= sched_deadline <= sched_period!");
-        return Err(Errno::EINVAL);
-    };
-    if runtime_ns < 1024 || deadline_ns < 1024 || period_ns < 1024 {
-        println!("Error: params are y1024");
+    if !((runtime_ns <= deadline_ns) && (deadline_ns <= period_ns)) || runtime_ns < 1024 {
         return Err(Errno::EINVAL);
     }
     let att_batch = Attributes {
         policy: Policy::Deadline,
-        nice: 0,
         deadline_ns,
         period_ns,
-        flags: SchedFlags::empty(),
-        priority: 0,
         runtime_ns,
-        sched_util_min: 0,
-        sched_util_max: 0,
+        ..Default::default()
     };
     set_attr(pid, att_batch)
 }
 
+/// Like `set_deadline()`, but sets `SCHED_FLAG_DL_OVERRUN`, so that the process
+/// receives a `SIGXCPU` signal on each run-time overrun.
+pub fn set_deadline_overrun_notify(
+    pid: Pid,
+    deadline_ns: u64,
+    period_ns: u64,
+    runtime_ns: u64,
+) -> Result<(), SchedError> {
+    let attr = Attributes::for_deadline(runtime_ns, deadline_ns, period_ns)?.with_dl_overrun();
+    set_attr(pid, attr)?;
+    Ok(())
+}
+
+/// Like `set_deadline()`, but sets `SCHED_FLAG_RECLAIM`, so that the thread may
+/// reclaim bandwidth unused by other real-time threads.
+pub fn set_deadline_reclaim(
+    pid: Pid,
+    deadline_ns: u64,
+    period_ns: u64,
+    runtime_ns: u64,
+) -> Result<(), SchedError> {
+    let attr = Attributes::for_deadline(runtime_ns, deadline_ns, period_ns)?.with_reclaim();
+    set_attr(pid, attr)?;
+    Ok(())
+}
+
+/// Combines `set_deadline_overrun_notify()` and `set_deadline_reclaim()`.
+pub fn set_deadline_overrun_and_reclaim(
+    pid: Pid,
+    deadline_ns: u64,
+    period_ns: u64,
+    runtime_ns: u64,
+) -> Result<(), SchedError> {
+    let attr = Attributes::for_deadline(runtime_ns, deadline_ns, period_ns)?
+        .with_dl_overrun()
+        .with_reclaim();
+    set_attr(pid, attr)?;
+    Ok(())
+}
+
+/// Replaces the flags of the thread whose ID is specified in `pid`, e.g. to
+/// enable `SCHED_FLAG_RECLAIM` for a running `SCHED_DEADLINE` thread, without
+/// disturbing its runtime, deadline and period.
+///
+/// The current attributes are read and written back with `new_flags`.
+/// `SCHED_FLAG_KEEP_PARAMS` cannot be used for this, as it also makes the kernel
+/// ignore the deadline flags.
+pub fn update_deadline_flags(pid: Pid, new_flags: SchedFlags) -> Result<(), SchedError> {
+    let mut attr = get_attr(pid)?;
+    attr.flags = new_flags;
+    set_attr(pid, attr)?;
+    Ok(())
+}
+
+/// Applies `attr` to every thread of the process whose ID is specified in
+/// `process_pid`. A failure does not stop the remaining threads from being
+/// configured; instead all threads that could not be configured are returned
+/// together with the error.
+pub fn apply_to_all_threads(process_pid: Pid, attr: Attributes) -> Result<(), Vec<(Pid, Errno)>> {
+    for_all_threads(process_pid, |tid| set_attr(tid, attr.clone()))
+}
+
+/// Returns the attributes of every thread of the process whose ID is specified
+/// in `process_pid`. Threads that exit while the list is read are skipped.
+pub fn get_attr_all_threads(process_pid: Pid) -> Result<Vec<(Pid, Attributes)>, io::Error> {
+    let mut attrs = Vec::new();
+    for tid in process_pid.all_threads()? {
+        match get_attr(tid) {
+            Ok(attr) => attrs.push((tid, attr)),
+            Err(Errno::ESRCH) => {}
+            Err(err) => return Err(io::Error::from_raw_os_error(err.into_raw())),
+        }
+    }
+    Ok(attrs)
+}
+
+/// Calls `f` for every thread of the process `process_pid` and collects the
+/// threads for which it failed. If the threads cannot be listed, the error is
+/// reported for `process_pid`.
+fn for_all_threads(
+    process_pid: Pid,
+    mut f: impl FnMut(Pid) -> Result<(), Errno>,
+) -> Result<(), Vec<(Pid, Errno)>> {
+    let threads = process_pid
+        .all_threads()
+        .map_err(|err| vec![(process_pid, errno_of(&err))])?;
+    let failures: Vec<(Pid, Errno)> = threads
+        .into_iter()
+        .filter_map(|tid| f(tid).err().map(|err| (tid, err)))
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+/// The errno of a failed procfs access, or `EIO` if there is none.
+fn errno_of(err: &io::Error) -> Errno {
+    Errno::new(err.raw_os_error().unwrap_or(Errno::EIO.into_raw()))
+}
+
+/// Sets the policy and static priority of the thread whose ID is specified in `pid`
+/// with the legacy `sched_setscheduler()` system call.
+///
+/// This interface cannot set the parameters of `Policy::Deadline`, use
+/// `set_deadline()` or `set_attr()` for it.
+pub fn set_scheduler(pid: Pid, policy: Policy, priority: u32) -> Result<(), Errno> {
+    let param = SchedParam {
+        sched_priority: priority as c_int,
+    };
+    unsafe { sched_setscheduler(pid.as_raw(), policy.into_raw() as c_int, &param) }.and(Ok(()))
+}
+
+/// Returns the policy and static priority of the thread whose ID is specified in
+/// `pid` with the legacy `sched_getscheduler()` and `sched_getparam()` system calls.
+///
+/// The parameters of `Policy::Deadline` are not available through this interface,
+/// use `get_attr()` for them.
+pub fn get_scheduler(pid: Pid) -> Result<(Policy, u32), Errno> {
+    let raw = unsafe { sched_getscheduler(pid.as_raw()) }? as u32 & !SCHED_RESET_ON_FORK;
+    let policy = Policy::from_raw(raw).map_err(|_| Errno::EINVAL)?;
+    let mut param = SchedParam::default();
+    unsafe { sched_getparam(pid.as_raw(), &mut param) }?;
+    Ok((policy, param.sched_priority as u32))
+}
+
+/// Returns the static priority of the thread whose ID is specified in `pid`
+/// with the legacy `sched_getparam()` system call.
+pub fn get_param(pid: Pid) -> Result<i32, Errno> {
+    let mut param = SchedParam::default();
+    unsafe { sched_getparam(pid.as_raw(), &mut param) }?;
+    Ok(param.sched_priority)
+}
+
+/// Sets the static priority of the thread whose ID is specified in `pid` with
+/// the legacy `sched_setparam()` system call. The policy is not changed, so the
+/// priority must be valid for the current policy of the thread.
+pub fn set_param(pid: Pid, priority: i32) -> Result<(), Errno> {
+    let param = SchedParam {
+        sched_priority: priority,
+    };
+    unsafe { sched_setparam(pid.as_raw(), &param) }.and(Ok(()))
+}
+
 pub fn get_priority_max(pol: Policy) -> Result<usize, Errno> {
     unsafe { sched::sched_get_priority_max(pol.into_raw() as c_int) }
 }
@@ -324,10 +856,89 @@ pub fn get_priority_min(pol: Policy) -> Result<usize, Errno> {
     unsafe { sched::sched_get_priority_min(pol.into_raw() as c_int) }
 }
 
+/// Range of static priorities that is valid for a policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl PriorityRange {
+    pub fn contains(&self, prio: u32) -> bool {
+        (self.min..=self.max).contains(&prio)
+    }
+}
+
+static PRIORITY_RANGES: Mutex<[Option<PriorityRange>; 8]> = Mutex::new([None; 8]);
+
+/// Returns the range of valid static priorities for `policy`, as reported by
+/// `get_priority_min()` and `get_priority_max()`. The ranges do not change at
+/// runtime, so they are only queried once per policy.
+pub fn priority_range(policy: Policy) -> Result<PriorityRange, Errno> {
+    let idx = policy.as_raw() as usize;
+    let mut ranges = PRIORITY_RANGES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if let Some(range) = ranges[idx] {
+        return Ok(range);
+    }
+    let range = PriorityRange {
+        min: get_priority_min(policy)? as u32,
+        max: get_priority_max(policy)? as u32,
+    };
+    ranges[idx] = Some(range);
+    Ok(range)
+}
+
+/// Checks that `priority` is a valid static priority for `policy`. Instead of
+/// silently clamping, an out of range value is reported as
+/// `SchedError::PriorityOutOfRange` together with the valid range.
+pub fn clamp_to_valid_priority(policy: Policy, priority: u32) -> Result<u32, SchedError> {
+    let range = priority_range(policy)?;
+    if range.contains(priority) {
+        Ok(priority)
+    } else {
+        Err(SchedError::PriorityOutOfRange { priority, range })
+    }
+}
+
+/// Maps `fraction` linearly onto the valid priorities of `policy`, where `0.0`
+/// is the lowest and `1.0` the highest priority, so that e.g. a medium
+/// `Policy::Fifo` priority can be requested without knowing the range. The
+/// result is rounded to the nearest priority.
+pub fn priority_for_fraction(policy: Policy, fraction: f64) -> Result<u32, SchedError> {
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(SchedError::FractionOutOfRange(fraction));
+    }
+    let range = priority_range(policy)?;
+    let offset = (fraction * (range.max - range.min) as f64).round() as u32;
+    Ok(range.min + offset)
+}
+
 pub fn sched_yield() -> Result<(), Errno> {
     unsafe { sched::sched_yield() }.and(Ok(()))
 }
 
+/// Calls `sched_yield()` `n` times.
+pub fn yield_n(n: usize) -> Result<(), Errno> {
+    for _ in 0..n {
+        sched_yield()?;
+    }
+    Ok(())
+}
+
+/// Calls `sched_yield()` as long as `condition` returns `true`. The condition is
+/// checked before each yield, so this returns immediately if it is `false`.
+///
+/// Note that a real-time thread only yields to threads of the same priority, so
+/// the thread that makes `condition` false must not run at a lower priority.
+pub fn yield_while<F: Fn() -> bool>(condition: F) -> Result<(), Errno> {
+    while condition() {
+        sched_yield()?;
+    }
+    Ok(())
+}
+
 pub fn set_affinity(pid: Pid, set: CpuSet) -> Result<(), Errno> {
     unsafe { sched_set_affinity(pid.as_raw(), CpuSet::size_of(), set.as_raw()).and(Ok(())) }
 }
@@ -338,6 +949,109 @@ pub fn get_affinity(pid: Pid) -> Result<CpuSet, Errno> {
         .and(Ok(cpuset))
 }
 
+/// Sets the CPU affinity of the thread whose ID is specified in `pid` from a CPU
+/// list in the format accepted by taskset(1), e.g. `0-3,8`.
+pub fn set_affinity_from_str(pid: Pid, mask: &str) -> Result<(), SchedError> {
+    set_affinity(pid, mask.parse()?)?;
+    Ok(())
+}
+
+/// Returns the CPU affinity of the thread whose ID is specified in `pid` as a CPU
+/// list, e.g. `0-3,8`.
+pub fn get_affinity_as_str(pid: Pid) -> Result<String, Errno> {
+    get_affinity(pid).map(|set| set.to_range_string())
+}
+
+/// Sets the CPU affinity of every thread of the process whose ID is specified
+/// in `process_pid`, as listed in `/proc/[pid]/task`. A failure does not stop
+/// the remaining threads from being configured; instead all threads whose
+/// affinity could not be set are returned together with the error.
+pub fn set_all_thread_affinity(process_pid: Pid, set: CpuSet) -> Result<(), Vec<(Pid, Errno)>> {
+    for_all_threads(process_pid, |tid| set_affinity(tid, set))
+}
+
+/// Sets the CPU affinity of every thread of every process in the process group
+/// `pgid`, or in the group of the calling process if `pgid` is zero. The
+/// members are found through the process group in `/proc/[pid]/stat`.
+///
+/// A failure does not stop the remaining threads from being configured; instead
+/// all threads whose affinity could not be set are returned together with the
+/// error. If the processes cannot be listed, the error is reported for `pgid`.
+pub fn set_affinity_for_process_group(pgid: Pid, set: CpuSet) -> Result<(), Vec<(Pid, Errno)>> {
+    let members = process_group_members(pgid).map_err(|err| vec![(pgid, errno_of(&err))])?;
+    let mut failures = Vec::new();
+    for pid in members {
+        if let Err(errors) = set_all_thread_affinity(pid, set) {
+            failures.extend(errors);
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+/// Lists the processes in the process group `pgid`, or in the group of the
+/// calling process if `pgid` is zero. Processes that exit while the list is
+/// read, or whose stat cannot be parsed, are skipped.
+fn process_group_members(pgid: Pid) -> Result<Vec<Pid>, io::Error> {
+    let pgid = match pgid.as_raw() {
+        0 => process_group_of(&fs::read_to_string("/proc/self/stat")?)?,
+        pgid => pgid,
+    };
+    let mut members = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let Some(pid) = entry?.file_name().to_str().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        let Ok(stat) = fs::read_to_string(format!("/proc/{pid}/stat")) else {
+            continue;
+        };
+        let Ok(group) = process_group_of(&stat) else {
+            continue;
+        };
+        if group == pgid {
+            members.push(Pid(pid));
+        }
+    }
+    Ok(members)
+}
+
+/// Returns the process group, field 5, from the content of `/proc/[pid]/stat`.
+fn process_group_of(stat: &str) -> Result<pid_t, io::Error> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed stat");
+    // the command name may contain spaces and parentheses, skip past its end
+    let (_, fields) = stat.rsplit_once(')').ok_or_else(invalid)?;
+    // the first field after the command name is field 3 (state)
+    fields
+        .split_whitespace()
+        .nth(5 - 3)
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(invalid)
+}
+
+/// Returns the indices of all CPUs the thread whose ID is specified in `pid` is
+/// allowed to run on, in ascending order.
+pub fn get_affinity_vec(pid: Pid) -> Result<Vec<usize>, Errno> {
+    get_affinity(pid).map(|set| set.iter().collect())
+}
+
+/// Runs `f` in a new thread with `SIGXCPU` ignored, so that an overrun of a
+/// `SCHED_FLAG_DL_OVERRUN` thread cannot kill the test binary. The thread is
+/// switched back to `SCHED_OTHER` before it exits.
+#[cfg(test)]
+pub(crate) fn in_deadline_thread<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    unsafe { libc::signal(libc::SIGXCPU, libc::SIG_IGN) };
+    std::thread::spawn(move || {
+        let result = f();
+        set_other(Pid::this(), 0).unwrap();
+        result
+    })
+    .join()
+    .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::sched::*;
@@ -361,6 +1075,122 @@ mod tests {
         assert_eq!(a.policy, Policy::Batch);
         assert_eq!(a.nice, 4);
     }
+    #[test]
+    fn test_policy_try_from() {
+        for policy in [Policy::Normal, Policy::Fifo, Policy::Deadline, Policy::Ext] {
+            assert_eq!(Policy::try_from(policy.as_raw()).unwrap(), policy);
+        }
+        assert!(matches!(
+            Policy::try_from(42),
+            Err(SchedError::UnknownPolicy(42))
+        ));
+        assert!(Policy::from_raw(42).is_err());
+    }
+
+    #[test]
+    fn test_get_attr_compat() {
+        let attr = get_attr_compat(Pid::this()).unwrap();
+        let expected = get_attr(Pid::this()).unwrap();
+        assert_eq!(format!("{attr:?}"), format!("{expected:?}"));
+        assert!(matches!(
+            get_attr_compat(Pid::new(-1)),
+            Err(SchedError::Errno(Errno::EINVAL))
+        ));
+    }
+
+    #[test]
+    fn test_ext() {
+        if !is_ext_available() {
+            // The errno depends on the kernel version and configuration.
+            assert!(set_ext(Pid::this()).is_err());
+            return;
+        }
+        set_ext(Pid::this()).unwrap();
+        assert_eq!(get_attr(Pid::this()).unwrap().policy, Policy::Ext);
+        set_other(Pid::this(), 0).unwrap();
+    }
+
+    #[test]
+    fn test_sched_flags_default() {
+        assert_eq!(SchedFlags::default(), SchedFlags::empty());
+    }
+
+    #[test]
+    fn test_attributes_default() {
+        let attr = Attributes::default();
+        assert_eq!(attr.policy, Policy::Normal);
+        assert_eq!(attr.flags, SchedFlags::empty());
+        assert_eq!((attr.nice, attr.priority), (0, 0));
+        assert_eq!(
+            (attr.runtime_ns, attr.deadline_ns, attr.period_ns),
+            (0, 0, 0)
+        );
+        assert_eq!((attr.sched_util_min, attr.sched_util_max), (0, 0));
+    }
+
+    #[test]
+    fn test_pid_as_ref() {
+        fn raw(pid: impl AsRef<pid_t>) -> pid_t {
+            *pid.as_ref()
+        }
+        assert_eq!(raw(Pid::new(42)), 42);
+        let pid = Pid::new(7);
+        let borrowed: &pid_t = pid.borrow();
+        assert_eq!(*borrowed, 7);
+    }
+
+    #[test]
+    fn test_yield_helpers() {
+        yield_n(0).unwrap();
+        yield_n(3).unwrap();
+
+        let calls = std::cell::Cell::new(0);
+        yield_while(|| {
+            calls.set(calls.get() + 1);
+            calls.get() < 4
+        })
+        .unwrap();
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = Attributes {
+            policy: Policy::Fifo,
+            nice: 0,
+            deadline_ns: 0,
+            period_ns: 0,
+            flags: SchedFlags::SCHED_FLAG_RESET_ON_FORK,
+            priority: 50,
+            runtime_ns: 0,
+            sched_util_min: 0,
+            sched_util_max: 0,
+        };
+        let override_with = Attributes {
+            policy: Policy::Normal,
+            nice: 0,
+            deadline_ns: 0,
+            period_ns: 0,
+            flags: SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX,
+            priority: 0,
+            runtime_ns: 0,
+            sched_util_min: 0,
+            sched_util_max: 512,
+        };
+        let merged = Attributes::merge(&base, &override_with);
+        assert_eq!(merged.policy, Policy::Fifo);
+        assert_eq!(merged.priority, 50);
+        assert_eq!(merged.flags, SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX);
+        assert_eq!(merged.sched_util_min, 0);
+        assert_eq!(merged.sched_util_max, 512);
+
+        assert_eq!(
+            Attributes::diff(&base, &merged),
+            vec!["flags", "sched_util_max"]
+        );
+        assert!(Attributes::diff(&merged, &merged).is_empty());
+    }
+
     #[test]
     fn test_setter() {
         set_other(Pid::this(), -20).unwrap();
@@ -412,12 +1242,353 @@ mod tests {
         sched_yield().unwrap();
     }
 
+    #[test]
+    fn test_deadline_overrun_notify() {
+        let a = in_deadline_thread(|| {
+            set_deadline_overrun_notify(Pid::this(), 1_000_000, 1_000_000, 50_000).unwrap();
+            get_attr(Pid::this()).unwrap()
+        });
+        assert_eq!(a.policy, Policy::Deadline);
+        assert!(a.flags.contains(SchedFlags::SCHED_FLAG_DL_OVERRUN));
+    }
+
+    #[test]
+    fn test_deadline_flags_invalid() {
+        assert!(matches!(
+            set_deadline_overrun_notify(Pid::this(), 1_000_000, 1_000_000, 2_000_000),
+            Err(SchedError::InvalidAttributes(_))
+        ));
+        assert!(matches!(
+            set_deadline_reclaim(Pid::this(), 1_000_000, 1_000_000, 100),
+            Err(SchedError::InvalidAttributes(_))
+        ));
+        assert_eq!(
+            set_deadline(Pid::this(), 1_000_000, 500_000, 50_000),
+            Err(Errno::EINVAL)
+        );
+    }
+
+    #[test]
+    fn test_deadline_reclaim() {
+        let (reclaim, both) = in_deadline_thread(|| {
+            set_deadline_reclaim(Pid::this(), 1_000_000, 1_000_000, 50_000).unwrap();
+            let reclaim = get_attr(Pid::this()).unwrap();
+            set_deadline_overrun_and_reclaim(Pid::this(), 1_000_000, 1_000_000, 50_000).unwrap();
+            (reclaim, get_attr(Pid::this()).unwrap())
+        });
+        assert!(reclaim.flags.contains(SchedFlags::SCHED_FLAG_RECLAIM));
+        assert!(!reclaim.flags.contains(SchedFlags::SCHED_FLAG_DL_OVERRUN));
+        assert!(both.flags.contains(SchedFlags::SCHED_FLAG_RECLAIM));
+        assert!(both.flags.contains(SchedFlags::SCHED_FLAG_DL_OVERRUN));
+    }
+
+    #[test]
+    fn test_update_deadline_flags() {
+        set_deadline(Pid::this(), 1_000_000, 2_000_000, 50_000).unwrap();
+        update_deadline_flags(Pid::this(), SchedFlags::SCHED_FLAG_RECLAIM).unwrap();
+        let a = get_attr(Pid::this()).unwrap();
+        assert_eq!(a.policy, Policy::Deadline);
+        assert!(a.flags.contains(SchedFlags::SCHED_FLAG_RECLAIM));
+        assert_eq!(
+            (a.runtime_ns, a.deadline_ns, a.period_ns),
+            (50_000, 1_000_000, 2_000_000)
+        );
+
+        update_deadline_flags(Pid::this(), SchedFlags::empty()).unwrap();
+        let a = get_attr(Pid::this()).unwrap();
+        assert!(!a.flags.contains(SchedFlags::SCHED_FLAG_RECLAIM));
+        assert_eq!(a.runtime_ns, 50_000);
+    }
+
+    #[test]
+    fn test_scheduler() {
+        set_scheduler(Pid::this(), Policy::RoundRobin, 42).unwrap();
+        assert_eq!(
+            get_scheduler(Pid::this()).unwrap(),
+            (Policy::RoundRobin, 42)
+        );
+        set_scheduler(Pid::this(), Policy::Normal, 0).unwrap();
+        assert_eq!(get_scheduler(Pid::this()).unwrap(), (Policy::Normal, 0));
+        assert!(set_scheduler(Pid::this(), Policy::Deadline, 0).is_err());
+    }
+
     #[test]
     fn test_prio() {
         get_priority_max(Policy::Fifo).unwrap();
         get_priority_min(Policy::Fifo).unwrap();
     }
 
+    #[test]
+    fn test_priority_range() {
+        let range = priority_range(Policy::Fifo).unwrap();
+        assert_eq!(range, PriorityRange { min: 1, max: 99 });
+        assert_eq!(priority_range(Policy::Fifo).unwrap(), range);
+        assert!(range.contains(1) && range.contains(99));
+        assert!(!range.contains(0) && !range.contains(100));
+        assert_eq!(
+            priority_range(Policy::Normal).unwrap(),
+            PriorityRange { min: 0, max: 0 }
+        );
+    }
+
+    #[test]
+    fn test_priority_for_fraction() {
+        assert_eq!(priority_for_fraction(Policy::Fifo, 0.0).unwrap(), 1);
+        assert_eq!(priority_for_fraction(Policy::Fifo, 0.5).unwrap(), 50);
+        assert_eq!(priority_for_fraction(Policy::RoundRobin, 1.0).unwrap(), 99);
+        assert_eq!(priority_for_fraction(Policy::Normal, 0.7).unwrap(), 0);
+        for fraction in [-0.1, 1.5, f64::NAN] {
+            assert!(matches!(
+                priority_for_fraction(Policy::Fifo, fraction),
+                Err(SchedError::FractionOutOfRange(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_attributes_constructors() {
+        let fifo = Attributes::for_fifo(10).unwrap();
+        assert_eq!((fifo.policy, fifo.priority), (Policy::Fifo, 10));
+        assert_eq!(Attributes::for_rr(99).unwrap().policy, Policy::RoundRobin);
+        assert!(matches!(
+            Attributes::for_fifo(0),
+            Err(SchedError::PriorityOutOfRange { priority: 0, .. })
+        ));
+        let dl = Attributes::for_deadline(500_000, 1_000_000, 10_000_000).unwrap();
+        assert_eq!(
+            (dl.runtime_ns, dl.deadline_ns, dl.period_ns),
+            (500_000, 1_000_000, 10_000_000)
+        );
+        assert!(Attributes::for_deadline(500_000, 1_000_000, 0).is_ok());
+        assert!(matches!(
+            Attributes::for_deadline(2_000_000, 1_000_000, 10_000_000),
+            Err(SchedError::InvalidAttributes(_))
+        ));
+        assert!(Attributes::for_deadline(100, 1_000_000, 10_000_000).is_err());
+        assert_eq!(Attributes::for_normal(-5).unwrap().nice, -5);
+        assert!(Attributes::for_batch(20).is_err());
+        assert_eq!(Attributes::for_idle().unwrap().policy, Policy::Idle);
+    }
+
+    #[test]
+    fn test_attributes_flag_builders() {
+        let a = Attributes::for_deadline(500_000, 1_000_000, 10_000_000)
+            .unwrap()
+            .with_reset_on_fork()
+            .with_dl_overrun()
+            .with_reclaim();
+        assert_eq!(
+            a.flags,
+            SchedFlags::SCHED_FLAG_RESET_ON_FORK
+                | SchedFlags::SCHED_FLAG_DL_OVERRUN
+                | SchedFlags::SCHED_FLAG_RECLAIM
+        );
+        let a = Attributes::for_normal(0).unwrap().with_util_clamp(128, 512);
+        assert_eq!((a.sched_util_min, a.sched_util_max), (128, 512));
+        assert!(a.flags.contains(
+            SchedFlags::SCHED_FLAG_UTIL_CLAMP_MIN | SchedFlags::SCHED_FLAG_UTIL_CLAMP_MAX
+        ));
+        a.validate().unwrap();
+    }
+
+    #[test]
+    fn test_attributes_display() {
+        let dl = Attributes::for_deadline(500_000, 1_000_000, 10_000_000).unwrap();
+        assert_eq!(
+            dl.to_string(),
+            "SCHED_DEADLINE runtime=500µs deadline=1ms period=10ms"
+        );
+        let mut fifo = Attributes::for_fifo(50).unwrap();
+        assert_eq!(fifo.to_string(), "SCHED_FIFO priority=50");
+        fifo.flags = SchedFlags::SCHED_FLAG_RESET_ON_FORK;
+        assert_eq!(
+            fifo.to_string(),
+            "SCHED_FIFO priority=50 flags=SCHED_FLAG_RESET_ON_FORK"
+        );
+        assert_eq!(
+            Attributes::for_normal(-5).unwrap().to_string(),
+            "SCHED_OTHER nice=-5"
+        );
+        assert_eq!(Attributes::for_idle().unwrap().to_string(), "SCHED_IDLE");
+    }
+
+    #[test]
+    fn test_pid_of_thread() {
+        let main = Pid::of_main_thread();
+        assert_eq!(main.as_raw() as u32, std::process::id());
+        let tid = Pid::of_current_thread();
+        assert_eq!(tid.as_raw(), unsafe { libc::gettid() });
+        let other = std::thread::spawn(|| (Pid::of_current_thread(), Pid::of_main_thread()))
+            .join()
+            .unwrap();
+        assert_ne!(other.0, tid);
+        assert_eq!(other.1, main);
+        assert_ne!(Pid::this(), tid);
+    }
+
+    #[test]
+    fn test_param() {
+        std::thread::spawn(|| {
+            assert_eq!(get_param(Pid::this()).unwrap(), 0);
+            assert_eq!(set_param(Pid::this(), 10), Err(Errno::EINVAL));
+            set_scheduler(Pid::this(), Policy::Fifo, 10).unwrap();
+            set_param(Pid::this(), 20).unwrap();
+            assert_eq!(get_param(Pid::this()).unwrap(), 20);
+            assert_eq!(get_scheduler(Pid::this()).unwrap(), (Policy::Fifo, 20));
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_clamp_to_valid_priority() {
+        assert_eq!(clamp_to_valid_priority(Policy::Fifo, 50).unwrap(), 50);
+        assert!(matches!(
+            clamp_to_valid_priority(Policy::RoundRobin, 100),
+            Err(SchedError::PriorityOutOfRange {
+                priority: 100,
+                range: PriorityRange { min: 1, max: 99 }
+            })
+        ));
+        assert!(clamp_to_valid_priority(Policy::Normal, 1).is_err());
+    }
+
+    #[test]
+    fn test_affinity_vec() {
+        let cpus = get_affinity_vec(Pid::this()).unwrap();
+        assert!(!cpus.is_empty());
+        let set = get_affinity(Pid::this()).unwrap();
+        assert!(cpus.iter().all(|&cpu| set.is_set(cpu)));
+    }
+
+    #[test]
+    fn test_all_threads() {
+        let tid = unsafe { libc::gettid() };
+        let threads = Pid::this().all_threads().unwrap();
+        assert!(threads.contains(&Pid::new(tid)));
+    }
+
+    #[test]
+    fn test_apply_to_all_threads() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+        let pid = Pid::new(child.id() as pid_t);
+        let att = Attributes {
+            policy: Policy::Batch,
+            nice: 7,
+            deadline_ns: 0,
+            period_ns: 0,
+            flags: SchedFlags::empty(),
+            priority: 0,
+            runtime_ns: 0,
+            sched_util_min: 0,
+            sched_util_max: 0,
+        };
+        let ret = apply_to_all_threads(pid, att);
+        let a = get_attr(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        ret.unwrap();
+        let a = a.unwrap();
+        assert_eq!(a.policy, Policy::Batch);
+        assert_eq!(a.nice, 7);
+
+        assert!(apply_to_all_threads(Pid::new(-1), a).is_err());
+    }
+
+    #[test]
+    fn test_get_attr_all_threads() {
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let worker = std::thread::spawn(move || {
+            set_fifo(Pid::this(), 15).unwrap();
+            let tid = Pid::of_current_thread();
+            rx.recv().unwrap();
+            tid
+        });
+        let attrs = loop {
+            let attrs = get_attr_all_threads(Pid::this()).unwrap();
+            if attrs
+                .iter()
+                .any(|(_, a)| a.policy == Policy::Fifo && a.priority == 15)
+            {
+                break attrs;
+            }
+            sched_yield().unwrap();
+        };
+        tx.send(()).unwrap();
+        let tid = worker.join().unwrap();
+        assert!(attrs.iter().any(|(t, _)| *t == tid));
+        assert!(attrs.iter().any(|(t, _)| *t == Pid::of_main_thread()));
+        assert!(get_attr_all_threads(Pid::new(-1)).is_err());
+    }
+
+    #[test]
+    fn test_affinity_str() {
+        let before = get_affinity(Pid::this()).unwrap();
+        let first = before.iter().next().unwrap();
+        set_affinity_from_str(Pid::this(), &first.to_string()).unwrap();
+        assert_eq!(get_affinity_as_str(Pid::this()).unwrap(), first.to_string());
+        assert!(matches!(
+            set_affinity_from_str(Pid::this(), "x"),
+            Err(SchedError::ParseCpuSet(_))
+        ));
+        set_affinity(Pid::this(), before).unwrap();
+    }
+
+    #[test]
+    fn test_affinity_for_process_group() {
+        use std::os::unix::process::CommandExt;
+
+        let mut leader = std::process::Command::new("sleep")
+            .arg("10")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pgid = leader.id() as pid_t;
+        let mut member = std::process::Command::new("sleep")
+            .arg("10")
+            .process_group(pgid)
+            .spawn()
+            .unwrap();
+        let set = get_affinity(Pid::this()).unwrap();
+        let first = CpuSet::empty().set(set.iter().next().unwrap());
+        let ret = set_affinity_for_process_group(Pid::new(pgid), first);
+        let affinities = [
+            get_affinity(Pid::new(pgid)),
+            get_affinity(Pid::new(member.id() as pid_t)),
+        ];
+        for child in [&mut leader, &mut member] {
+            child.kill().unwrap();
+            child.wait().unwrap();
+        }
+        ret.unwrap();
+        assert_eq!(affinities, [Ok(first), Ok(first)]);
+        assert_eq!(process_group_of("7 (a) b) S 1 42 42 0").unwrap(), 42);
+    }
+
+    #[test]
+    fn test_set_all_thread_affinity() {
+        // Changing the affinity of this process would race with other tests.
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+        let pid = Pid::new(child.id() as pid_t);
+        let set = get_affinity(Pid::this()).unwrap();
+        let last = CpuSet::empty().set(set.iter().last().unwrap());
+        let ret = set_all_thread_affinity(pid, last);
+        let affinity = get_affinity(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        ret.unwrap();
+        assert_eq!(affinity.unwrap(), last);
+
+        let failures = set_all_thread_affinity(Pid::new(-1), last).unwrap_err();
+        assert_eq!(failures.len(), 1);
+    }
+
     // #[test]
     // fn test_affinity() {
     //     let mut set = get_affinity(Pid::this()).unwrap();
@@ -0,0 +1,475 @@
+use std::{fs, io};
+
+use crate::{Pid, Policy};
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Scheduler statistics of a thread as reported by `/proc/[pid]/schedstat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SchedStats {
+    /// Time spent on the CPU in nanoseconds.
+    pub cpu_time_ns: u64,
+    /// Time spent waiting on a runqueue in nanoseconds.
+    pub wait_time_ns: u64,
+    /// Number of timeslices run on this CPU.
+    pub timeslices: u64,
+}
+
+impl SchedStats {
+    /// Returns the statistics accumulated between the reads of `earlier` and
+    /// `later`. Counters that decreased, e.g. because the PID was reused by
+    /// another thread in between, saturate to 0.
+    pub fn delta(earlier: &SchedStats, later: &SchedStats) -> SchedStats {
+        SchedStats {
+            cpu_time_ns: later.cpu_time_ns.saturating_sub(earlier.cpu_time_ns),
+            wait_time_ns: later.wait_time_ns.saturating_sub(earlier.wait_time_ns),
+            timeslices: later.timeslices.saturating_sub(earlier.timeslices),
+        }
+    }
+
+    fn parse(content: &str) -> Result<SchedStats, io::Error> {
+        let mut fields = content.split_whitespace().map(|field| {
+            field
+                .parse::<u64>()
+                .map_err(|_| invalid_data("malformed schedstat"))
+        });
+        let mut next = || {
+            fields
+                .next()
+                .unwrap_or_else(|| Err(invalid_data("truncated schedstat")))
+        };
+        Ok(SchedStats {
+            cpu_time_ns: next()?,
+            wait_time_ns: next()?,
+            timeslices: next()?,
+        })
+    }
+}
+
+/// Reads the scheduler statistics of the thread whose ID is specified in `pid`.
+/// Requires a kernel built with `CONFIG_SCHED_INFO`.
+pub fn get_sched_stats(pid: Pid) -> Result<SchedStats, io::Error> {
+    SchedStats::parse(&fs::read_to_string(pid.proc_path("schedstat"))?)
+}
+
+/// Context switch counters of a thread as reported by `/proc/[pid]/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ContextSwitchStats {
+    /// Number of times the thread gave up the CPU, e.g. to wait for a resource.
+    pub voluntary: u64,
+    /// Number of times the thread was preempted. Real-time threads should keep
+    /// this at or close to zero.
+    pub nonvoluntary: u64,
+}
+
+/// Returns the value of the field `name` in the content of a `/proc/[pid]/status`
+/// file.
+pub(crate) fn status_field<'a>(status: &'a str, name: &str) -> Option<&'a str> {
+    status.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key == name).then(|| value.trim())
+    })
+}
+
+impl ContextSwitchStats {
+    fn parse(status: &str) -> Result<ContextSwitchStats, io::Error> {
+        let field = |name| {
+            status_field(status, name)
+                .ok_or_else(|| invalid_data("missing context switch counter"))?
+                .parse::<u64>()
+                .map_err(|_| invalid_data("malformed context switch counter"))
+        };
+        Ok(ContextSwitchStats {
+            voluntary: field("voluntary_ctxt_switches")?,
+            nonvoluntary: field("nonvoluntary_ctxt_switches")?,
+        })
+    }
+}
+
+/// Reads the context switch counters of the thread whose ID is specified in `pid`.
+pub fn get_context_switches(pid: Pid) -> Result<ContextSwitchStats, io::Error> {
+    ContextSwitchStats::parse(&fs::read_to_string(pid.proc_path("status"))?)
+}
+
+const AT_CLKTCK: usize = 17;
+
+/// Returns the frequency of the clock ticks used in procfs, i.e. the value of
+/// `sysconf(_SC_CLK_TCK)`, taken from the auxiliary vector of the process.
+fn clock_ticks_per_second() -> Result<u64, io::Error> {
+    const WORD: usize = core::mem::size_of::<usize>();
+    let auxv = fs::read("/proc/self/auxv")?;
+    let ticks = auxv.chunks_exact(2 * WORD).find_map(|entry| {
+        let key = usize::from_ne_bytes(entry[..WORD].try_into().unwrap());
+        let value = usize::from_ne_bytes(entry[WORD..].try_into().unwrap());
+        (key == AT_CLKTCK).then_some(value as u64)
+    });
+    match ticks {
+        Some(0) => Err(invalid_data("AT_CLKTCK is zero")),
+        Some(ticks) => Ok(ticks),
+        None => Err(invalid_data("missing AT_CLKTCK")),
+    }
+}
+
+/// Returns the user and system time from the content of `/proc/[pid]/stat` in
+/// clock ticks.
+fn parse_cpu_ticks(stat: &str) -> Result<(u64, u64), io::Error> {
+    // the command name may contain spaces and parentheses, skip past its end
+    let (_, fields) = stat
+        .rsplit_once(')')
+        .ok_or_else(|| invalid_data("malformed stat"))?;
+    // the first field after the command name is field 3 (state)
+    let mut fields = fields.split_whitespace().skip(14 - 3);
+    let mut next = || {
+        fields
+            .next()
+            .and_then(|field| field.parse::<u64>().ok())
+            .ok_or_else(|| invalid_data("malformed stat"))
+    };
+    Ok((next()?, next()?))
+}
+
+/// Returns the CPU time consumed by the thread whose ID is specified in `pid`,
+/// i.e. the sum of user and system time from `/proc/[pid]/stat`, in nanoseconds.
+/// The resolution is limited to a clock tick, usually 10 ms.
+pub fn get_cpu_usage_ns(pid: Pid) -> Result<u64, io::Error> {
+    let (utime, stime) = parse_cpu_ticks(&fs::read_to_string(pid.proc_path("stat"))?)?;
+    let ticks = utime as u128 + stime as u128;
+    let ns = ticks * 1_000_000_000 / clock_ticks_per_second()? as u128;
+    u64::try_from(ns).map_err(|_| invalid_data("CPU time overflows u64 nanoseconds"))
+}
+
+/// Runqueue of a CPU as reported by `/proc/sched_debug`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedDebugEntry {
+    pub cpu: usize,
+    /// Number of runnable tasks on the CPU.
+    pub nr_running: u32,
+    /// Runnable tasks of the CPU.
+    pub tasks: Vec<SchedDebugTask>,
+}
+
+/// A runnable task as listed in `/proc/sched_debug`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedDebugTask {
+    pub pid: u32,
+    /// Policy derived from `prio`: `Deadline` for negative priorities, `Fifo` for
+    /// real-time priorities and `Normal` otherwise. The file does not allow to
+    /// tell `Fifo` from `RoundRobin` or `Normal` from `Batch` and `Idle`.
+    pub policy: Policy,
+    /// Kernel internal priority: -1 for deadline tasks, 0-99 for real-time tasks
+    /// (lower is more important) and 100-139 for other tasks.
+    pub prio: i32,
+}
+
+/// Positions of the columns of the `runnable tasks` table that are parsed, counted
+/// among the numeric columns following the PID.
+///
+/// The layout depends on the kernel version: before 6.6 the PID is followed by
+/// `tree-key switches prio`, since EEVDF by `vruntime eligible deadline slice
+/// sum-exec switches prio`, so the positions are taken from the header line.
+#[derive(Debug, Clone, Copy)]
+struct SchedDebugColumns {
+    switches: usize,
+    prio: usize,
+}
+
+impl SchedDebugColumns {
+    /// Parses the header line, e.g. ` S  task   PID  tree-key  switches  prio ...`.
+    fn parse(header: &str) -> Option<SchedDebugColumns> {
+        // `eligible` is the only column after the PID whose values are not
+        // numbers (`E` or `N`), rows are matched against the numeric ones only.
+        let columns: Vec<&str> = header
+            .split_whitespace()
+            .skip_while(|&column| column != "PID")
+            .skip(1)
+            .filter(|&column| column != "eligible")
+            .collect();
+        let position = |name| columns.iter().position(|&column| column == name);
+        Some(SchedDebugColumns {
+            switches: position("switches")?,
+            prio: position("prio")?,
+        })
+    }
+}
+
+impl SchedDebugTask {
+    /// Parses a line of the `runnable tasks` table. The command name may contain
+    /// spaces, so the row is located through the columns following it: the PID,
+    /// a fractional first column and integer `switches` and `prio` columns at the
+    /// positions given by the header.
+    fn parse(line: &str, columns: SchedDebugColumns) -> Option<SchedDebugTask> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        (1..tokens.len()).find_map(|i| {
+            let pid = tokens[i].parse::<u32>().ok()?;
+            // Skips the eligibility and custom slice markers of EEVDF kernels.
+            let values: Vec<&str> = tokens[i + 1..]
+                .iter()
+                .copied()
+                .filter(|token| token.parse::<f64>().is_ok())
+                .collect();
+            values.first().filter(|value| value.contains('.'))?;
+            values.get(columns.switches)?.parse::<u64>().ok()?;
+            let prio = values.get(columns.prio)?.parse::<i32>().ok()?;
+            let policy = match prio {
+                ..=-1 => Policy::Deadline,
+                0..=99 => Policy::Fifo,
+                _ => Policy::Normal,
+            };
+            Some(SchedDebugTask { pid, policy, prio })
+        })
+    }
+}
+
+fn parse_sched_debug(content: &str) -> Vec<SchedDebugEntry> {
+    let mut entries: Vec<SchedDebugEntry> = Vec::new();
+    let mut in_tasks = false;
+    let mut columns = None;
+    let mut has_nr_running = false;
+    for line in content.lines() {
+        if let Some(cpu) = line.strip_prefix("cpu#") {
+            let cpu = cpu
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .unwrap_or("");
+            if let Ok(cpu) = cpu.parse() {
+                entries.push(SchedDebugEntry {
+                    cpu,
+                    nr_running: 0,
+                    tasks: Vec::new(),
+                });
+                in_tasks = false;
+                has_nr_running = false;
+            }
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+        if in_tasks {
+            if trimmed.is_empty() {
+                in_tasks = false;
+            } else if let Some(columns) = columns {
+                entry.tasks.extend(SchedDebugTask::parse(line, columns));
+            } else {
+                columns = SchedDebugColumns::parse(line);
+            }
+        } else if trimmed.starts_with("runnable tasks:") {
+            in_tasks = true;
+            columns = None;
+        } else if let Some((key, value)) = trimmed.split_once(':') {
+            // the runqueue of the CPU is listed first, followed by the per class
+            // runqueues, which have a .nr_running of their own
+            if key.trim() == ".nr_running" && !has_nr_running {
+                entry.nr_running = value.trim().parse().unwrap_or(0);
+                has_nr_running = true;
+            }
+        }
+    }
+    entries
+}
+
+/// Reads the per-CPU runqueues from `/proc/sched_debug`, or from
+/// `/sys/kernel/debug/sched/debug` on kernels since 5.13, which require debugfs to
+/// be mounted.
+pub fn read_sched_debug() -> Result<Vec<SchedDebugEntry>, io::Error> {
+    let content = match fs::read_to_string("/proc/sched_debug") {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            fs::read_to_string("/sys/kernel/debug/sched/debug")?
+        }
+        content => content?,
+    };
+    Ok(parse_sched_debug(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sched_stats() {
+        assert_eq!(
+            SchedStats::parse("1234 56 7\n").unwrap(),
+            SchedStats {
+                cpu_time_ns: 1234,
+                wait_time_ns: 56,
+                timeslices: 7
+            }
+        );
+        assert!(SchedStats::parse("1234 56\n").is_err());
+        assert!(SchedStats::parse("1234 abc 7\n").is_err());
+    }
+
+    #[test]
+    fn test_sched_stats_delta() {
+        let earlier = SchedStats::parse("1000 50 3").unwrap();
+        let later = SchedStats::parse("1500 80 5").unwrap();
+        assert_eq!(
+            SchedStats::delta(&earlier, &later),
+            SchedStats {
+                cpu_time_ns: 500,
+                wait_time_ns: 30,
+                timeslices: 2
+            }
+        );
+        assert_eq!(SchedStats::delta(&later, &earlier), SchedStats::default());
+    }
+
+    #[test]
+    fn test_sched_stats() {
+        get_sched_stats(Pid::this()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_context_switches() {
+        let status = "Name:\ttest\nvoluntary_ctxt_switches:\t12\nnonvoluntary_ctxt_switches:\t3\n";
+        assert_eq!(
+            ContextSwitchStats::parse(status).unwrap(),
+            ContextSwitchStats {
+                voluntary: 12,
+                nonvoluntary: 3
+            }
+        );
+        assert!(ContextSwitchStats::parse("Name:\ttest\n").is_err());
+        get_context_switches(Pid::this()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_cpu_ticks() {
+        let stat = "42 (a (b) c) S 1 42 42 0 -1 4194304 100 0 0 0 25 17 0 0 20 0 1 0 1 0 0";
+        assert_eq!(parse_cpu_ticks(stat).unwrap(), (25, 17));
+        assert!(parse_cpu_ticks("42 (a) S 1 42").is_err());
+    }
+
+    #[test]
+    fn test_parse_sched_debug() {
+        let content = "\
+Sched Debug Version: v0.11, 6.5.0 #1
+ktime                                   : 1234.567890
+
+cpu#0, 2400.000 MHz
+  .nr_running                    : 2
+  .nr_switches                   : 1000
+
+cfs_rq[0]:/
+  .nr_running                    : 1
+
+runnable tasks:
+ S            task   PID         tree-key  switches  prio     wait-time             sum-exec        sum-sleep
+-------------------------------------------------------------------------------------------------------------
+ S        systemd     1      5374.609164      2435   120         0.000000       911.384843         0.000000 0 0 /
+>R    rt worker   321         0.000000        10    49         0.000000         1.000000         0.000000 0 0 /
+ S        dl_task   322         0.000000         5    -1         0.000000         1.000000         0.000000 0 0 /
+
+cpu#1, 2400.000 MHz
+  .nr_running                    : 0
+
+runnable tasks:
+ S            task   PID         tree-key  switches  prio     wait-time             sum-exec        sum-sleep
+-------------------------------------------------------------------------------------------------------------
+";
+        let entries = parse_sched_debug(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].cpu, 0);
+        assert_eq!(entries[0].nr_running, 2);
+        assert_eq!(
+            entries[0].tasks,
+            vec![
+                SchedDebugTask {
+                    pid: 1,
+                    policy: Policy::Normal,
+                    prio: 120
+                },
+                SchedDebugTask {
+                    pid: 321,
+                    policy: Policy::Fifo,
+                    prio: 49
+                },
+                SchedDebugTask {
+                    pid: 322,
+                    policy: Policy::Deadline,
+                    prio: -1
+                },
+            ]
+        );
+        assert_eq!(entries[1].cpu, 1);
+        assert_eq!(entries[1].nr_running, 0);
+        assert!(entries[1].tasks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_sched_debug_eevdf() {
+        let content = "\
+Sched Debug Version: v0.11, 6.18.0 #1
+ktime                                   : 1234.567890
+
+cpu#0, 2400.000 MHz
+  .nr_running                    : 2
+
+runnable tasks:
+ S            task   PID       vruntime   eligible    deadline             slice          sum-exec      switches  prio         wait-time        sum-sleep       sum-block  node   group-id  group-path
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+ S        systemd     1    638708.030225   E      638708.719285           0.700000        79.887665       264     120         0.000000         0.000000         0.000000   0      0        /
+>R      rt worker   321     42246.229858   E       42246.897301           0.700000         1.000000        10      49         0.000000         0.000000         0.000000   0      0        /
+ S        dl_task   322         0.000000   N           0.000000 S         0.100000         1.000000         5      -1         0.000000         0.000000         0.000000   0      0        /
+
+cpu#1, 2400.000 MHz
+  .nr_running                    : 0
+
+runnable tasks:
+ S            task   PID       vruntime   eligible    deadline             slice          sum-exec      switches  prio         wait-time        sum-sleep       sum-block  node   group-id  group-path
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+";
+        let entries = parse_sched_debug(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].nr_running, 2);
+        assert_eq!(
+            entries[0].tasks,
+            vec![
+                SchedDebugTask {
+                    pid: 1,
+                    policy: Policy::Normal,
+                    prio: 120
+                },
+                SchedDebugTask {
+                    pid: 321,
+                    policy: Policy::Fifo,
+                    prio: 49
+                },
+                SchedDebugTask {
+                    pid: 322,
+                    policy: Policy::Deadline,
+                    prio: -1
+                },
+            ]
+        );
+        assert!(entries[1].tasks.is_empty());
+    }
+
+    #[test]
+    fn test_read_sched_debug() {
+        // Since 5.13 the file is only available if debugfs is mounted, and
+        // debugfs is only readable by root.
+        let entries = match read_sched_debug() {
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                return
+            }
+            entries => entries.unwrap(),
+        };
+        assert!(entries.iter().any(|entry| !entry.tasks.is_empty()));
+    }
+
+    #[test]
+    fn test_cpu_usage() {
+        let expected = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        assert_eq!(clock_ticks_per_second().unwrap(), expected as u64);
+        get_cpu_usage_ns(Pid::this()).unwrap();
+    }
+}
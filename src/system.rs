@@ -0,0 +1,353 @@
+use std::{fs, io};
+
+use crate::{
+    get_affinity, get_rt_period_us, get_rt_runtime_us, sched_affinity_mask_from_hex, CpuSet, Pid,
+};
+
+/// Returns `true` if the running kernel is a PREEMPT_RT kernel, i.e. if
+/// `/sys/kernel/realtime` exists and contains `1`.
+///
+/// On other kernels `SCHED_DEADLINE` and `SCHED_FIFO` tasks may experience
+/// considerably higher latencies.
+pub fn is_realtime_kernel() -> bool {
+    fs::read_to_string("/sys/kernel/realtime")
+        .map(|content| content.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// Reads a file containing a CPU list, e.g. `0-3,8`.
+pub(crate) fn read_cpu_list(path: &str) -> Result<CpuSet, io::Error> {
+    fs::read_to_string(path)?
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Reads a file containing a hexadecimal CPU mask, e.g. `00000000,0000ff0f`.
+pub(crate) fn read_cpu_mask(path: &str) -> Result<CpuSet, io::Error> {
+    sched_affinity_mask_from_hex(&fs::read_to_string(path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Formats `set` as a hexadecimal mask in the format the kernel reads, with a
+/// comma after every 32 CPUs, e.g. `00000001,0000ff0f`.
+fn kernel_cpu_mask(set: &CpuSet) -> String {
+    let hex = set.to_hex_string();
+    let padded = format!("{hex:0>width$}", width = hex.len().div_ceil(8) * 8);
+    (0..padded.len())
+        .step_by(8)
+        .map(|start| &padded[start..start + 8])
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns the number of CPUs that are currently online, as listed in
+/// `/sys/devices/system/cpu/online`.
+pub fn online_cpu_count() -> Result<usize, io::Error> {
+    read_cpu_list("/sys/devices/system/cpu/online").map(|set| set.count())
+}
+
+/// Returns the number of CPUs that could ever be online on this system, as listed
+/// in `/sys/devices/system/cpu/possible`.
+pub fn possible_cpu_count() -> Result<usize, io::Error> {
+    read_cpu_list("/sys/devices/system/cpu/possible").map(|set| set.count())
+}
+
+/// Returns the CPUs isolated with the `isolcpus=` kernel parameter, as listed in
+/// `/sys/devices/system/cpu/isolated`.
+///
+/// Fails with `io::ErrorKind::NotFound` on kernels without that file; use
+/// `get_isolated_cpus()` to treat this as no isolated CPUs.
+pub fn isolated_cpus() -> Result<CpuSet, io::Error> {
+    read_cpu_list("/sys/devices/system/cpu/isolated")
+}
+
+/// Like `read_cpu_list()`, but returns an empty set if the file does not exist
+/// or the kernel reports `(null)` for an unset mask.
+fn read_optional_cpu_list(path: &str) -> Result<CpuSet, io::Error> {
+    match fs::read_to_string(path) {
+        Ok(content) if content.trim() == "(null)" => Ok(CpuSet::empty()),
+        Ok(content) => content
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(CpuSet::empty()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Like `isolated_cpus()`, but returns an empty set instead of failing with
+/// `io::ErrorKind::NotFound` if the kernel does not provide
+/// `/sys/devices/system/cpu/isolated`. All other errors are returned as is.
+pub fn get_isolated_cpus() -> Result<CpuSet, io::Error> {
+    match isolated_cpus() {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(CpuSet::empty()),
+        result => result,
+    }
+}
+
+/// Returns the CPUs that run without the periodic scheduler tick, as configured
+/// with the `nohz_full=` kernel parameter and listed in
+/// `/sys/devices/system/cpu/nohz_full`. Returns an empty set if the kernel is
+/// not configured for it.
+pub fn get_nohz_full_cpus() -> Result<CpuSet, io::Error> {
+    read_optional_cpu_list("/sys/devices/system/cpu/nohz_full")
+}
+
+/// Position of a CPU in the CPU topology of the system.
+#[derive(Debug)]
+pub struct CpuTopology {
+    /// ID of the physical core, unique only within the package.
+    pub core_id: usize,
+    /// ID of the physical package (socket).
+    pub package_id: usize,
+    /// CPUs that are hardware threads of the same core, including this one.
+    pub thread_siblings: CpuSet,
+    /// CPUs in the same physical package, including this one.
+    pub core_siblings: CpuSet,
+}
+
+pub(crate) fn read_value<T: std::str::FromStr>(path: &str) -> Result<T, io::Error>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Reads the topology of `cpu` from `/sys/devices/system/cpu/cpu{N}/topology/`.
+pub fn cpu_topology(cpu: usize) -> Result<CpuTopology, io::Error> {
+    let dir = format!("/sys/devices/system/cpu/cpu{cpu}/topology");
+    Ok(CpuTopology {
+        core_id: read_value(&format!("{dir}/core_id"))?,
+        package_id: read_value(&format!("{dir}/physical_package_id"))?,
+        thread_siblings: read_cpu_list(&format!("{dir}/thread_siblings_list"))?,
+        core_siblings: read_cpu_list(&format!("{dir}/core_siblings_list"))?,
+    })
+}
+
+/// Returns the NUMA node `cpu` belongs to, found through the
+/// `/sys/devices/system/cpu/cpu{N}/node{M}` link. Systems without NUMA support
+/// have no such link, in which case `io::ErrorKind::NotFound` is returned.
+pub fn numa_node_for_cpu(cpu: usize) -> Result<usize, io::Error> {
+    for entry in fs::read_dir(format!("/sys/devices/system/cpu/cpu{cpu}"))? {
+        let name = entry?.file_name();
+        let node = name.to_str().and_then(|name| name.strip_prefix("node"));
+        if let Some(node) = node.and_then(|node| node.parse().ok()) {
+            return Ok(node);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "CPU is not assigned to a NUMA node",
+    ))
+}
+
+/// Returns the CPUs of NUMA node `node`, read from
+/// `/sys/devices/system/node/node{N}/cpumap`.
+pub fn cpus_for_numa_node(node: usize) -> Result<CpuSet, io::Error> {
+    read_cpu_mask(&format!("/sys/devices/system/node/node{node}/cpumap"))
+}
+
+fn read_cpufreq(cpu: usize, file: &str) -> Result<u64, io::Error> {
+    read_value(&format!("/sys/devices/system/cpu/cpu{cpu}/cpufreq/{file}"))
+}
+
+/// Returns the current frequency of `cpu` in kHz, read from
+/// `/sys/devices/system/cpu/cpu{N}/cpufreq/scaling_cur_freq`. Fails if no cpufreq
+/// driver is loaded.
+pub fn cpu_frequency_khz(cpu: usize) -> Result<u64, io::Error> {
+    read_cpufreq(cpu, "scaling_cur_freq")
+}
+
+/// Returns the maximum frequency the governor may select for `cpu` in kHz.
+pub fn cpu_max_frequency_khz(cpu: usize) -> Result<u64, io::Error> {
+    read_cpufreq(cpu, "scaling_max_freq")
+}
+
+/// Returns the minimum frequency the governor may select for `cpu` in kHz.
+pub fn cpu_min_frequency_khz(cpu: usize) -> Result<u64, io::Error> {
+    read_cpufreq(cpu, "scaling_min_freq")
+}
+
+/// The system-wide settings relevant for real-time applications, as returned by
+/// `get_system_rt_info()`.
+#[derive(Debug, Clone)]
+pub struct SystemRtInfo {
+    pub online_cpus: usize,
+    pub is_preempt_rt: bool,
+    pub rt_period_us: u64,
+    /// [`RT_UNLIMITED`](crate::RT_UNLIMITED) if real-time tasks are not throttled.
+    pub rt_runtime_us: u64,
+    pub isolated_cpus: CpuSet,
+}
+
+/// Reads the number of online CPUs, the kernel type, the real-time bandwidth
+/// and the isolated CPUs in one call.
+pub fn get_system_rt_info() -> Result<SystemRtInfo, io::Error> {
+    Ok(SystemRtInfo {
+        online_cpus: online_cpu_count()?,
+        is_preempt_rt: is_realtime_kernel(),
+        rt_period_us: get_rt_period_us()?,
+        rt_runtime_us: get_rt_runtime_us()?,
+        isolated_cpus: get_isolated_cpus()?,
+    })
+}
+
+/// Returns the CPUs that may handle interrupt `irq`, read from
+/// `/proc/irq/{irq}/smp_affinity`.
+pub fn get_irq_affinity(irq: u32) -> Result<CpuSet, io::Error> {
+    read_cpu_mask(&format!("/proc/irq/{irq}/smp_affinity"))
+}
+
+/// Restricts interrupt `irq` to the CPUs in `set` by writing
+/// `/proc/irq/{irq}/smp_affinity`. Requires root; the kernel may also reject
+/// the mask, e.g. for per-CPU interrupts or if `set` contains no online CPU.
+pub fn set_irq_affinity(irq: u32, set: CpuSet) -> Result<(), io::Error> {
+    fs::write(
+        format!("/proc/irq/{irq}/smp_affinity"),
+        kernel_cpu_mask(&set),
+    )
+}
+
+/// Iterates over all processes in `/proc` whose CPU affinity shares at least
+/// one CPU with a filter set, e.g. to list the processes that may run on an
+/// isolated CPU. Yields the PID together with the affinity.
+///
+/// The affinity of a process is the one of its main thread. Processes whose
+/// affinity cannot be read, e.g. because they exited in the meantime, are
+/// skipped.
+#[derive(Debug)]
+pub struct ProcessAffinityIterator {
+    entries: fs::ReadDir,
+    filter: CpuSet,
+}
+
+impl ProcessAffinityIterator {
+    /// Lists the processes whose affinity intersects `filter`.
+    pub fn new(filter: CpuSet) -> Result<Self, io::Error> {
+        Ok(Self {
+            entries: fs::read_dir("/proc")?,
+            filter,
+        })
+    }
+}
+
+impl Iterator for ProcessAffinityIterator {
+    type Item = (Pid, CpuSet);
+
+    fn next(&mut self) -> Option<(Pid, CpuSet)> {
+        for entry in self.entries.by_ref() {
+            let Some(pid) = entry
+                .ok()
+                .and_then(|entry| entry.file_name().to_str()?.parse().ok())
+            else {
+                continue;
+            };
+            let pid = Pid::new(pid);
+            match get_affinity(pid) {
+                Ok(set) if set.count_common_with(&self.filter) > 0 => return Some((pid, set)),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realtime_kernel() {
+        let expected = fs::read_to_string("/sys/kernel/realtime").is_ok_and(|c| c.trim() == "1");
+        assert_eq!(is_realtime_kernel(), expected);
+    }
+
+    #[test]
+    fn test_cpu_count() {
+        let online = online_cpu_count().unwrap();
+        assert!(online > 0);
+        assert!(possible_cpu_count().unwrap() >= online);
+        assert!(isolated_cpus().unwrap().count() < online);
+    }
+
+    #[test]
+    fn test_isolation() {
+        assert!(get_isolated_cpus().unwrap().count() < online_cpu_count().unwrap());
+        assert!(get_nohz_full_cpus().unwrap().count() < online_cpu_count().unwrap());
+        assert_eq!(
+            read_optional_cpu_list("/nonexistent").unwrap(),
+            CpuSet::empty()
+        );
+    }
+
+    #[test]
+    fn test_process_affinity_iterator() {
+        let own = Pid::of_main_thread();
+        let affinity = get_affinity(own).unwrap();
+        let found: Vec<_> = ProcessAffinityIterator::new(affinity).unwrap().collect();
+        assert!(found.contains(&(own, affinity)));
+        assert!(found
+            .iter()
+            .all(|(_, set)| set.count_common_with(&affinity) > 0));
+        assert_eq!(
+            ProcessAffinityIterator::new(CpuSet::empty())
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_cpu_topology() {
+        let topology = cpu_topology(0).unwrap();
+        assert!(topology.thread_siblings.is_set(0));
+        assert!(topology.core_siblings.is_set(0));
+        assert!(cpu_topology(CpuSet::size_of() * 8).is_err());
+    }
+
+    #[test]
+    fn test_numa_node() {
+        if let Ok(node) = numa_node_for_cpu(0) {
+            assert!(cpus_for_numa_node(node).unwrap().is_set(0));
+        }
+        assert!(numa_node_for_cpu(CpuSet::size_of() * 8).is_err());
+    }
+
+    #[test]
+    fn test_system_rt_info() {
+        let info = get_system_rt_info().unwrap();
+        assert_eq!(info.online_cpus, online_cpu_count().unwrap());
+        assert_eq!(info.is_preempt_rt, is_realtime_kernel());
+        assert!(info.rt_period_us > 0);
+        assert!(info.isolated_cpus.count() < info.online_cpus);
+    }
+
+    #[test]
+    fn test_irq_affinity() {
+        assert_eq!(kernel_cpu_mask(&CpuSet::empty()), "00000000");
+        let set = CpuSet::empty().set(0).set(3).set(40);
+        assert_eq!(kernel_cpu_mask(&set), "00000100,00000009");
+        assert_eq!(
+            sched_affinity_mask_from_hex(&kernel_cpu_mask(&set)).unwrap(),
+            set
+        );
+        if let Ok(affinity) = get_irq_affinity(0) {
+            assert!(affinity.count() > 0);
+        }
+        assert!(get_irq_affinity(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_cpu_frequency() {
+        if fs::metadata("/sys/devices/system/cpu/cpu0/cpufreq/scaling_cur_freq").is_ok() {
+            let min = cpu_min_frequency_khz(0).unwrap();
+            let max = cpu_max_frequency_khz(0).unwrap();
+            assert!(min <= max);
+            assert!(cpu_frequency_khz(0).unwrap() > 0);
+        } else {
+            assert!(cpu_frequency_khz(0).is_err());
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use syscalls::Errno;
+
+use crate::{
+    lock_all_memory, Attributes, ClockId, CpuSet, PeriodicTimer, Policy, SchedFlags, ThreadBuilder,
+    TimeSpec,
+};
+
+/// Describes how a real-time thread is configured when it is spawned with
+/// `spawn()`.
+#[derive(Debug, Clone)]
+pub struct RtTaskSpec {
+    policy: Policy,
+    priority: u32,
+    affinity: Option<CpuSet>,
+    name: Option<String>,
+    stack_size: Option<usize>,
+    mem_lock: bool,
+}
+
+impl RtTaskSpec {
+    /// A thread scheduled with `policy` at the static `priority`.
+    pub fn new(policy: Policy, priority: u32) -> Self {
+        Self {
+            policy,
+            priority,
+            affinity: None,
+            name: None,
+            stack_size: None,
+            mem_lock: false,
+        }
+    }
+
+    /// Pins the thread to the CPUs in `set`.
+    pub fn affinity(mut self, set: CpuSet) -> Self {
+        self.affinity = Some(set);
+        self
+    }
+
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Locks all memory of the process with `lock_all_memory()` before the
+    /// thread is spawned, so that its stack is locked as well.
+    pub fn mem_lock(mut self, mem_lock: bool) -> Self {
+        self.mem_lock = mem_lock;
+        self
+    }
+
+    fn attributes(&self) -> Attributes {
+        Attributes {
+            policy: self.policy,
+            flags: SchedFlags::empty(),
+            nice: 0,
+            priority: self.priority,
+            runtime_ns: 0,
+            deadline_ns: 0,
+            period_ns: 0,
+            sched_util_min: 0,
+            sched_util_max: 0,
+        }
+    }
+}
+
+/// Spawns a thread configured according to `spec` that runs `f`.
+///
+/// Linux cannot set the policy of a thread before it starts, so the thread
+/// applies its affinity and scheduling attributes itself before `f` runs, see
+/// `ThreadBuilder`. Errors while applying the spec are returned and `f` is not
+/// run in that case.
+pub fn spawn<F, T>(spec: RtTaskSpec, f: F) -> io::Result<JoinHandle<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    if spec.mem_lock {
+        lock_all_memory().map_err(|err| io::Error::from_raw_os_error(err.into_raw()))?;
+    }
+    let mut builder = ThreadBuilder::new().scheduling(spec.attributes());
+    if let Some(set) = spec.affinity {
+        builder = builder.affinity(set);
+    }
+    if let Some(name) = spec.name {
+        builder = builder.name(name);
+    }
+    if let Some(size) = spec.stack_size {
+        builder = builder.stack_size(size);
+    }
+    Ok(builder.spawn(f)?)
+}
+
+/// Spawns a watchdog thread scheduled with `policy` at `priority` that calls
+/// `callback` once every `period`, e.g. to reset a hardware watchdog. The
+/// thread runs until the process exits; use `spawn_watchdog_until()` for a
+/// watchdog that can be stopped.
+///
+/// The ticks are absolute deadlines on `ClockMonotonic`, see `PeriodicTimer`, so
+/// they do not drift. Usually the watchdog runs with `Policy::Fifo` at the
+/// highest priority of the system, so that it is not starved by the threads it
+/// supervises.
+pub fn spawn_watchdog(
+    policy: Policy,
+    priority: u32,
+    period: TimeSpec,
+    callback: impl Fn() + Send + 'static,
+) -> io::Result<JoinHandle<()>> {
+    spawn_watchdog_until(
+        policy,
+        priority,
+        period,
+        Arc::new(AtomicBool::new(false)),
+        callback,
+    )
+}
+
+/// Like `spawn_watchdog()`, but the thread returns after the first tick at
+/// which `stop` is set, without calling `callback` again, so it can be joined.
+pub fn spawn_watchdog_until(
+    policy: Policy,
+    priority: u32,
+    period: TimeSpec,
+    stop: Arc<AtomicBool>,
+    callback: impl Fn() + Send + 'static,
+) -> io::Result<JoinHandle<()>> {
+    let mut timer = PeriodicTimer::new(ClockId::ClockMonotonic, period)
+        .map_err(|err| io::Error::from_raw_os_error(err.into_raw()))?;
+    let spec = RtTaskSpec::new(policy, priority).name("watchdog".to_string());
+    spawn(spec, move || loop {
+        match timer.wait() {
+            Ok(_) if stop.load(Ordering::Acquire) => return,
+            Ok(_) => callback(),
+            // The tick is not consumed, so the next wait retries it.
+            Err(Errno::EINTR) => {}
+            Err(err) => panic!("watchdog timer failed: {err}"),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_affinity, get_attr, health::locked_memory_kb, Pid};
+
+    #[test]
+    fn test_spawn() {
+        let pinned = CpuSet::empty().set(get_affinity(Pid::this()).unwrap().iter().next().unwrap());
+        let spec = RtTaskSpec::new(Policy::RoundRobin, 30)
+            .affinity(pinned)
+            .name("rt-task".to_string())
+            .stack_size(256 * 1024);
+        let (name, attr, affinity) = spawn(spec, || {
+            (
+                std::thread::current().name().map(String::from),
+                get_attr(Pid::this()).unwrap(),
+                get_affinity(Pid::this()).unwrap(),
+            )
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+        assert_eq!(name.as_deref(), Some("rt-task"));
+        assert_eq!((attr.policy, attr.priority), (Policy::RoundRobin, 30));
+        assert_eq!(affinity, pinned);
+
+        let err = spawn(RtTaskSpec::new(Policy::Fifo, 200), || ()).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(22));
+    }
+
+    #[test]
+    fn test_spawn_mem_lock() {
+        // Memory locking applies to the whole process, so spawn in a forked child
+        // to keep it away from the other tests of this binary.
+        match unsafe { libc::fork() } {
+            0 => {
+                let spec = RtTaskSpec::new(Policy::Normal, 0).mem_lock(true);
+                let ok = match spawn(spec, || ()) {
+                    Ok(handle) => {
+                        handle.join().is_ok()
+                            && locked_memory_kb(Pid::this()).is_ok_and(|kb| kb > 0)
+                    }
+                    // Without `CAP_IPC_LOCK` the lock fails and nothing is spawned.
+                    Err(err) => matches!(err.raw_os_error(), Some(libc::EPERM | libc::ENOMEM)),
+                };
+                unsafe { libc::_exit(if ok { 0 } else { 1 }) }
+            }
+            child => {
+                assert!(child > 0);
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(libc::WEXITSTATUS(status), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_watchdog() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let period = TimeSpec::nanoseconds(1_000_000);
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = spawn_watchdog_until(Policy::Fifo, 10, period, stop.clone(), move || {
+            tx.send(get_attr(Pid::this()).map(|attr| attr.policy))
+                .unwrap();
+        })
+        .unwrap();
+        let policies = rx.iter().take(3).collect::<Vec<_>>();
+        stop.store(true, Ordering::Release);
+        handle.join().unwrap();
+        assert_eq!(policies, vec![Ok(Policy::Fifo); 3]);
+
+        let err = spawn_watchdog(Policy::Fifo, 200, period, || ()).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(22));
+    }
+}
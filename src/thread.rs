@@ -0,0 +1,130 @@
+use std::{
+    panic,
+    sync::mpsc,
+    thread::{self, JoinHandle},
+};
+
+use crate::{set_affinity, set_attr, Attributes, CpuSet, Pid, SchedError};
+
+/// Spawns threads whose scheduling attributes and CPU affinity are applied
+/// before the thread runs any user code. Wraps `std::thread::Builder`.
+#[derive(Debug)]
+pub struct ThreadBuilder {
+    builder: thread::Builder,
+    attr: Option<Attributes>,
+    affinity: Option<CpuSet>,
+}
+
+impl Default for ThreadBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThreadBuilder {
+    pub fn new() -> Self {
+        Self {
+            builder: thread::Builder::new(),
+            attr: None,
+            affinity: None,
+        }
+    }
+
+    /// Names the thread, see `std::thread::Builder::name()`.
+    pub fn name(mut self, name: String) -> Self {
+        self.builder = self.builder.name(name);
+        self
+    }
+
+    /// Sets the stack size of the thread, see `std::thread::Builder::stack_size()`.
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.builder = self.builder.stack_size(size);
+        self
+    }
+
+    /// Applies `attr` with `set_attr()` in the new thread.
+    pub fn scheduling(mut self, attr: Attributes) -> Self {
+        self.attr = Some(attr);
+        self
+    }
+
+    /// Applies `set` with `set_affinity()` in the new thread.
+    pub fn affinity(mut self, set: CpuSet) -> Self {
+        self.affinity = Some(set);
+        self
+    }
+
+    /// Spawns a thread that first applies the affinity and scheduling attributes
+    /// and then runs `f`.
+    ///
+    /// Waits until the setup of the thread is done. If it fails, `f` is never run
+    /// and the error is returned; the thread then exits on its own.
+    pub fn spawn<F, T>(self, f: F) -> Result<JoinHandle<T>, SchedError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (attr, affinity) = (self.attr, self.affinity);
+        let (setup_tx, setup_rx) = mpsc::channel();
+        let handle = self.builder.spawn(move || {
+            let setup = || -> Result<(), SchedError> {
+                if let Some(set) = affinity {
+                    set_affinity(Pid::this(), set)?;
+                }
+                if let Some(attr) = attr {
+                    set_attr(Pid::this(), attr)?;
+                }
+                Ok(())
+            };
+            let result = setup();
+            let failed = result.is_err();
+            let _ = setup_tx.send(result);
+            if failed {
+                // Unwinding without a panic skips the panic hook, so the failed
+                // thread terminates silently.
+                panic::resume_unwind(Box::new(()));
+            }
+            f()
+        })?;
+        setup_rx
+            .recv()
+            .expect("the thread reports its setup before running user code")?;
+        Ok(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_affinity, get_attr, Policy};
+
+    #[test]
+    fn test_thread_builder() {
+        let cpu = get_affinity(Pid::this()).unwrap().iter().next().unwrap();
+        let set = CpuSet::empty().set(cpu);
+        let handle = ThreadBuilder::new()
+            .name("rt".to_string())
+            .scheduling(Attributes::for_fifo(10).unwrap())
+            .affinity(set)
+            .spawn(|| {
+                let attr = get_attr(Pid::this()).unwrap();
+                let affinity = get_affinity(Pid::this()).unwrap();
+                (thread::current().name().map(String::from), attr, affinity)
+            })
+            .unwrap();
+        let (name, attr, affinity) = handle.join().unwrap();
+        assert_eq!(name.as_deref(), Some("rt"));
+        assert_eq!((attr.policy, attr.priority), (Policy::Fifo, 10));
+        assert_eq!(affinity, set);
+    }
+
+    #[test]
+    fn test_thread_builder_setup_error() {
+        let mut attr = Attributes::for_fifo(10).unwrap();
+        attr.priority = 200;
+        let result = ThreadBuilder::new()
+            .scheduling(attr)
+            .spawn(|| panic!("must not run"));
+        assert!(matches!(result, Err(SchedError::Errno(_))));
+    }
+}
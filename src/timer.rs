@@ -0,0 +1,117 @@
+use std::os::fd::{AsRawFd, RawFd};
+
+use syscalls::Errno;
+
+use crate::{
+    get_time,
+    lowlevel::timerfd::{close, timerfd_create, timerfd_read, timerfd_settime, TFD_CLOEXEC},
+    nanosleep_absolute, ClockId, ITimerSpec, TimeSpec,
+};
+
+/// A periodic timer backed by a `timerfd`. The file descriptor becomes readable
+/// on every expiration, so the timer can be used with `poll` or `epoll` as well.
+pub struct PeriodicFd {
+    fd: RawFd,
+}
+
+impl PeriodicFd {
+    /// Creates a timer on `clockid` that first expires one `period` from now and
+    /// then every `period`.
+    pub fn new(clockid: ClockId, period: TimeSpec) -> Result<Self, Errno> {
+        let fd = unsafe { timerfd_create(clockid.as_raw(), TFD_CLOEXEC) }?;
+        let timer = Self { fd };
+        let spec = ITimerSpec {
+            interval: period,
+            value: period,
+        };
+        unsafe { timerfd_settime(timer.fd, 0, &spec, core::ptr::null_mut()) }?;
+        Ok(timer)
+    }
+
+    /// Blocks until the next expiration and returns the number of expirations
+    /// since the last call. A value larger than 1 means that periods were missed.
+    pub fn wait(&self) -> Result<u64, Errno> {
+        unsafe { timerfd_read(self.fd) }
+    }
+}
+
+impl AsRawFd for PeriodicFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for PeriodicFd {
+    fn drop(&mut self) {
+        let _ = unsafe { close(self.fd) };
+    }
+}
+
+/// A periodic timer that sleeps with absolute deadlines on a clock, so that the
+/// ticks do not drift no matter how long the work between them takes.
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodicTimer {
+    clockid: ClockId,
+    period: TimeSpec,
+    next: TimeSpec,
+}
+
+impl PeriodicTimer {
+    /// Creates a timer on `clockid` whose first tick is one `period` from now.
+    pub fn new(clockid: ClockId, period: TimeSpec) -> Result<Self, Errno> {
+        Ok(Self {
+            clockid,
+            period,
+            next: get_time(clockid)? + period,
+        })
+    }
+
+    /// Sleeps until the next tick and returns the time the tick was scheduled for.
+    /// If the tick has already passed, returns immediately.
+    pub fn wait(&mut self) -> Result<TimeSpec, Errno> {
+        let tick = self.next;
+        nanosleep_absolute(self.clockid, tick)?;
+        self.next = tick + self.period;
+        Ok(tick)
+    }
+
+    pub fn clockid(&self) -> ClockId {
+        self.clockid
+    }
+
+    pub fn period(&self) -> TimeSpec {
+        self.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periodic_fd() {
+        let period = TimeSpec::nanoseconds(1_000_000);
+        let timer = PeriodicFd::new(ClockId::ClockMonotonic, period).unwrap();
+        assert!(timer.as_raw_fd() >= 0);
+
+        let start = get_time(ClockId::ClockMonotonic).unwrap();
+        let mut expirations = 0;
+        while expirations < 3 {
+            expirations += timer.wait().unwrap();
+        }
+        let elapsed = get_time(ClockId::ClockMonotonic).unwrap() - start;
+        assert!(elapsed.as_nanoseconds() >= 2 * period.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_periodic_timer() {
+        let period = TimeSpec::nanoseconds(1_000_000);
+        let mut timer = PeriodicTimer::new(ClockId::ClockMonotonic, period).unwrap();
+        let first = timer.wait().unwrap();
+        assert!(
+            get_time(ClockId::ClockMonotonic).unwrap().as_nanoseconds() >= first.as_nanoseconds()
+        );
+        let second = timer.wait().unwrap();
+        assert_eq!(second - first, period);
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+
+use syscalls::Errno;
+
+use crate::{get_time, ClockId, PeriodicTimer, TimeSpec};
+
+/// Measures the CPU time the calling thread spends in `f`, using
+/// `ClockThreadCputimeId`. Time the thread is preempted or sleeping is not
+/// included.
+pub fn measure_execution_time<F: FnOnce()>(f: F) -> TimeSpec {
+    measure_execution_time_with(ClockId::ClockThreadCputimeId, f)
+        .expect("the thread CPU-time clock is always available")
+}
+
+/// Like `measure_execution_time()`, but measures the time that passes on `clockid`,
+/// e.g. wall-clock time with `ClockMonotonic`.
+pub fn measure_execution_time_with<F: FnOnce()>(clockid: ClockId, f: F) -> Result<TimeSpec, Errno> {
+    let start = get_time(clockid)?;
+    f();
+    Ok(get_time(clockid)? - start)
+}
+
+/// Measures the time that passes on a clock.
+#[derive(Debug, Clone, Copy)]
+pub struct Stopwatch {
+    clockid: ClockId,
+    start: TimeSpec,
+}
+
+impl Stopwatch {
+    /// Starts a new stopwatch on `clockid`.
+    pub fn start(clockid: ClockId) -> Result<Self, Errno> {
+        Ok(Self {
+            clockid,
+            start: get_time(clockid)?,
+        })
+    }
+
+    /// Returns the time elapsed since the stopwatch was started or since the
+    /// previous lap, and starts the next lap.
+    pub fn lap(&mut self) -> Result<TimeSpec, Errno> {
+        let now = get_time(self.clockid)?;
+        let elapsed = now - self.start;
+        self.start = now;
+        Ok(elapsed)
+    }
+
+    /// Returns the time elapsed since the stopwatch was started or since the
+    /// previous lap, without starting a new lap. This allows taking several
+    /// samples relative to the same start.
+    pub fn split(&self) -> Result<TimeSpec, Errno> {
+        Ok(get_time(self.clockid)? - self.start)
+    }
+
+    /// Restarts the stopwatch at the current time.
+    pub fn reset(&mut self) -> Result<(), Errno> {
+        self.start = get_time(self.clockid)?;
+        Ok(())
+    }
+}
+
+/// Tracks the worst-case execution time (WCET) of a piece of code over many
+/// invocations, measured in CPU time of the calling thread.
+#[derive(Debug, Default)]
+pub struct WcetTracker {
+    stopwatch: Option<Stopwatch>,
+    wcet: TimeSpec,
+}
+
+impl WcetTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts measuring a sample.
+    pub fn start(&mut self) {
+        self.stopwatch = Some(
+            Stopwatch::start(ClockId::ClockThreadCputimeId)
+                .expect("the thread CPU-time clock is always available"),
+        );
+    }
+
+    /// Stops measuring and returns the sample. Panics if `start()` was not called.
+    pub fn stop(&mut self) -> TimeSpec {
+        let sample = self
+            .stopwatch
+            .take()
+            .expect("WcetTracker::stop() called without start()")
+            .lap()
+            .expect("the thread CPU-time clock is always available");
+        if sample.as_nanoseconds() > self.wcet.as_nanoseconds() {
+            self.wcet = sample;
+        }
+        sample
+    }
+
+    /// Returns the longest sample observed so far.
+    pub fn wcet(&self) -> TimeSpec {
+        self.wcet
+    }
+
+    /// Forgets all samples.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Records the wakeup jitter of a periodic task, i.e. how late it woke up
+/// compared to the tick it waited for. Both times are taken from `ClockMonotonic`,
+/// unless the clock of a timer is used, see `wait()`.
+#[derive(Debug, Default)]
+pub struct JitterTracker {
+    samples: Vec<TimeSpec>,
+}
+
+impl JitterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the jitter of a wakeup for a tick at `expected` and returns it.
+    pub fn record(&mut self, expected: TimeSpec) -> Result<TimeSpec, Errno> {
+        self.record_on(ClockId::ClockMonotonic, expected)
+    }
+
+    /// Waits for the next tick of `timer` and records the jitter of the wakeup,
+    /// measured on the clock of the timer.
+    pub fn wait(&mut self, timer: &mut PeriodicTimer) -> Result<TimeSpec, Errno> {
+        let tick = timer.wait()?;
+        self.record_on(timer.clockid(), tick)
+    }
+
+    fn record_on(&mut self, clockid: ClockId, expected: TimeSpec) -> Result<TimeSpec, Errno> {
+        let jitter = get_time(clockid)? - expected;
+        self.samples.push(jitter);
+        Ok(jitter)
+    }
+
+    /// Returns the largest recorded jitter, or zero if nothing was recorded.
+    pub fn max_jitter(&self) -> TimeSpec {
+        self.samples
+            .iter()
+            .copied()
+            .max_by_key(TimeSpec::as_nanoseconds)
+            .unwrap_or_default()
+    }
+
+    /// Returns the mean of all recorded jitters, or zero if nothing was recorded.
+    pub fn mean_jitter(&self) -> TimeSpec {
+        if self.samples.is_empty() {
+            return TimeSpec::zeroed();
+        }
+        let sum: i128 = self
+            .samples
+            .iter()
+            .map(|s| s.as_nanoseconds() as i128)
+            .sum();
+        TimeSpec::nanoseconds((sum / self.samples.len() as i128) as i64)
+    }
+
+    pub fn samples(&self) -> &[TimeSpec] {
+        &self.samples
+    }
+}
+
+/// A histogram of latencies with user-defined bucket boundaries.
+///
+/// With `n` boundaries there are `n + 1` buckets: bucket `i` counts latencies
+/// below `boundaries_ns[i]` (and at or above the previous boundary), the last
+/// bucket counts everything at or above the largest boundary.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    boundaries_ns: Vec<u64>,
+}
+
+impl LatencyHistogram {
+    pub fn new(boundaries_ns: &[u64]) -> Self {
+        let mut boundaries_ns = boundaries_ns.to_vec();
+        boundaries_ns.sort_unstable();
+        boundaries_ns.dedup();
+        Self {
+            buckets: vec![0; boundaries_ns.len() + 1],
+            boundaries_ns,
+        }
+    }
+
+    pub fn record(&mut self, latency_ns: u64) {
+        let idx = self.boundaries_ns.partition_point(|&b| b <= latency_ns);
+        self.buckets[idx] += 1;
+    }
+
+    /// Total number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns an upper bound for the `p`-th percentile (`0.0..=100.0`), i.e. the
+    /// upper boundary of the bucket it falls into. Returns `u64::MAX` if it falls
+    /// into the last bucket and 0 if nothing was recorded.
+    pub fn percentile(&self, p: f64) -> u64 {
+        let count = self.count();
+        if count == 0 {
+            return 0;
+        }
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * count as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return self.boundaries_ns.get(idx).copied().unwrap_or(u64::MAX);
+            }
+        }
+        u64::MAX
+    }
+
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    pub fn boundaries_ns(&self) -> &[u64] {
+        &self.boundaries_ns
+    }
+}
+
+impl fmt::Display for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WIDTH: u64 = 50;
+        let max = self.buckets.iter().copied().max().unwrap_or(0).max(1);
+        for (idx, &n) in self.buckets.iter().enumerate() {
+            let label = match self.boundaries_ns.get(idx) {
+                Some(b) => format!("< {b}"),
+                None => format!(">= {}", self.boundaries_ns.last().unwrap_or(&0)),
+            };
+            // n <= max, so the bar is at most WIDTH long, but n * WIDTH may not fit
+            // into a u64.
+            let bar = "#".repeat((n as u128 * WIDTH as u128).div_ceil(max as u128) as usize);
+            writeln!(f, "{label:>14} ns | {n:>8} {bar}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nanosleep_relative;
+
+    #[test]
+    fn test_measure_execution_time() {
+        let sleep = TimeSpec::nanoseconds(2_000_000);
+        let wall = measure_execution_time_with(ClockId::ClockMonotonic, || {
+            nanosleep_relative(ClockId::ClockMonotonic, sleep).unwrap()
+        })
+        .unwrap();
+        assert!(wall.as_nanoseconds() >= sleep.as_nanoseconds());
+
+        let cpu =
+            measure_execution_time(|| nanosleep_relative(ClockId::ClockMonotonic, sleep).unwrap());
+        assert!(cpu.as_nanoseconds() >= 0);
+        assert!(cpu.as_nanoseconds() < sleep.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_stopwatch() {
+        let mut stopwatch = Stopwatch::start(ClockId::ClockMonotonic).unwrap();
+        nanosleep_relative(ClockId::ClockMonotonic, TimeSpec::nanoseconds(1_000_000)).unwrap();
+        assert!(stopwatch.lap().unwrap().as_nanoseconds() >= 1_000_000);
+        assert!(stopwatch.lap().unwrap().as_nanoseconds() < 1_000_000);
+    }
+
+    #[test]
+    fn test_stopwatch_split() {
+        let mut stopwatch = Stopwatch::start(ClockId::ClockMonotonic).unwrap();
+        nanosleep_relative(ClockId::ClockMonotonic, TimeSpec::nanoseconds(1_000_000)).unwrap();
+        let first = stopwatch.split().unwrap();
+        assert!(first.as_nanoseconds() >= 1_000_000);
+        assert!(stopwatch.split().unwrap().as_nanoseconds() >= first.as_nanoseconds());
+        stopwatch.reset().unwrap();
+        assert!(stopwatch.split().unwrap().as_nanoseconds() < first.as_nanoseconds());
+    }
+
+    #[test]
+    fn test_wcet_tracker() {
+        let mut tracker = WcetTracker::new();
+        let mut max = 0;
+        for n in [1_000, 100_000, 10_000] {
+            tracker.start();
+            let mut x = 0u64;
+            for i in 0..n {
+                x = std::hint::black_box(x.wrapping_add(i));
+            }
+            max = max.max(tracker.stop().as_nanoseconds());
+        }
+        assert_eq!(tracker.wcet().as_nanoseconds(), max);
+        tracker.reset();
+        assert_eq!(tracker.wcet(), TimeSpec::zeroed());
+    }
+
+    #[test]
+    fn test_jitter_tracker() {
+        let mut tracker = JitterTracker::new();
+        assert_eq!(tracker.max_jitter(), TimeSpec::zeroed());
+        assert_eq!(tracker.mean_jitter(), TimeSpec::zeroed());
+
+        let mut timer =
+            PeriodicTimer::new(ClockId::ClockMonotonic, TimeSpec::nanoseconds(1_000_000)).unwrap();
+        for _ in 0..3 {
+            tracker.wait(&mut timer).unwrap();
+        }
+        assert_eq!(tracker.samples().len(), 3);
+        assert!(tracker.samples().iter().all(|s| s.as_nanoseconds() >= 0));
+        assert!(tracker.max_jitter().as_nanoseconds() >= tracker.mean_jitter().as_nanoseconds());
+
+        let mut timer =
+            PeriodicTimer::new(ClockId::ClockRealtime, TimeSpec::nanoseconds(1_000_000)).unwrap();
+        let jitter = tracker.wait(&mut timer).unwrap();
+        assert!((0..1_000_000_000).contains(&jitter.as_nanoseconds()));
+    }
+
+    #[test]
+    fn test_latency_histogram() {
+        let mut hist = LatencyHistogram::new(&[10_000, 1_000, 100_000]);
+        assert_eq!(hist.boundaries_ns(), &[1_000, 10_000, 100_000]);
+        assert_eq!(hist.percentile(50.0), 0);
+
+        for latency in [0, 999, 1_000, 5_000, 9_999, 50_000, 100_000, 1_000_000] {
+            hist.record(latency);
+        }
+        assert_eq!(hist.buckets(), &[2, 3, 1, 2]);
+        assert_eq!(hist.count(), 8);
+        assert_eq!(hist.percentile(0.0), 1_000);
+        assert_eq!(hist.percentile(25.0), 1_000);
+        assert_eq!(hist.percentile(50.0), 10_000);
+        assert_eq!(hist.percentile(75.0), 100_000);
+        assert_eq!(hist.percentile(100.0), u64::MAX);
+
+        let text = hist.to_string();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.lines().last().unwrap().contains(">= 100000"));
+
+        hist.buckets[0] = u64::MAX;
+        assert!(hist
+            .to_string()
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(&"#".repeat(50)));
+    }
+}